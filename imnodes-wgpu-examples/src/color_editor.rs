@@ -366,13 +366,13 @@ pub fn show(ui: &Ui, state: &mut State) {
 }
 
 /// main node ui
-fn create_the_editor(
+fn create_the_editor<'a>(
     ui: &Ui,
-    editor_context: &mut EditorContext,
+    editor_context: &'a mut EditorContext,
     graph: &mut Graph,
     id_gen: &mut IdentifierGenerator,
-) -> imnodes::ScopeNone {
-    editor(editor_context, |mut editor| {
+) -> imnodes::ScopeNone<'a> {
+    let (scope, ()) = editor(ui, editor_context, |mut editor| {
         let popup_modal = im_str!("popup_add_node");
 
         if editor.is_hovered()
@@ -587,5 +587,6 @@ fn create_the_editor(
         for Link { id, start, end } in &graph.links {
             editor.add_link(*id, *end, *start);
         }
-    })
+    });
+    scope
 }
@@ -5,7 +5,7 @@ use imnodes::{editor, PinShape};
 pub fn show(ui: &Ui, context: &mut imnodes::EditorContext) {
     let mut id_gen = context.new_identifier_generator();
 
-    editor(context, |mut editor| {
+    editor(ui, context, |mut editor| {
         editor.add_node(id_gen.next_node(), |mut node| {
             node.add_titlebar(|| {
                 ui.text(im_str!("simple node :)"));
@@ -76,7 +76,7 @@ pub fn show(ui: &Ui, state: &mut MultiEditState) {
 
     ui.text(im_str!("or you can press \"A\" or right click"));
 
-    let outer_scope = editor(editor_context, |mut editor| {
+    let (outer_scope, ()) = editor(ui, editor_context, |mut editor| {
         // TODO is_key_released should probably take a Key and do the lookup internally
         if editor.is_hovered()
             && (ui.is_key_released(ui.key_index(imgui::Key::A))
@@ -131,7 +131,7 @@ pub fn show(ui: &Ui, state: &mut MultiEditState) {
         })
     }
 
-    if let Some(link) = outer_scope.get_dropped_link() {
+    if let Some(link) = outer_scope.link_destroyed() {
         state
             .links
             .swap_remove(state.links.iter().position(|e| e.id == link).unwrap());
@@ -0,0 +1,101 @@
+use crate::{CoordinateSystem, NodeId, ScopeEditor};
+use imgui::{ImColor, Ui};
+
+/// small status icon drawn on the top right corner of a node, see [ScopeEditor::node_badges]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum NodeBadge {
+    /// red circle with a cross
+    Error,
+    /// yellow triangle with an exclamation mark
+    Warning,
+    /// green play symbol
+    Running,
+    /// dark red dot like in a debugger
+    Breakpoint,
+}
+
+/// radius of a [NodeBadge]
+const BADGE_RADIUS: f32 = 7.0;
+
+/// space between two badges of the same node
+const BADGE_SPACING: f32 = 3.0;
+
+const BADGE_ERROR: [f32; 3] = [0.85, 0.15, 0.15];
+const BADGE_WARNING: [f32; 3] = [0.95, 0.75, 0.1];
+const BADGE_RUNNING: [f32; 3] = [0.2, 0.75, 0.3];
+const BADGE_BREAKPOINT: [f32; 3] = [0.6, 0.05, 0.05];
+
+impl ScopeEditor<'_> {
+    /// draw status icons on the top right corner of a node, the first badge is on the corner
+    /// and the others follow to the left
+    ///
+    /// call it after the node was added in this frame, otherwise the badges are placed with the
+    /// position and size of the last frame
+    pub fn node_badges(&self, ui: &Ui, node: NodeId, badges: &[NodeBadge]) {
+        let position = node.get_position(CoordinateSystem::ScreenSpace);
+        let size = node.get_dimensions();
+        let draw_list = ui.get_window_draw_list();
+
+        let mut center = [position.x + size.x, position.y];
+        for badge in badges {
+            let [x, y] = center;
+            let r = BADGE_RADIUS;
+            match badge {
+                NodeBadge::Error => {
+                    draw_list
+                        .add_circle(center, r, ImColor::from(BADGE_ERROR))
+                        .filled(true)
+                        .build();
+                    let arm = 0.4 * r;
+                    let white = ImColor::from([1.0, 1.0, 1.0]);
+                    draw_list
+                        .add_line([x - arm, y - arm], [x + arm, y + arm], white)
+                        .thickness(2.0)
+                        .build();
+                    draw_list
+                        .add_line([x - arm, y + arm], [x + arm, y - arm], white)
+                        .thickness(2.0)
+                        .build();
+                }
+                NodeBadge::Warning => {
+                    draw_list
+                        .add_triangle(
+                            [x, y - r],
+                            [x + r, y + 0.8 * r],
+                            [x - r, y + 0.8 * r],
+                            ImColor::from(BADGE_WARNING),
+                        )
+                        .filled(true)
+                        .build();
+                    let black = ImColor::from([0.0, 0.0, 0.0]);
+                    draw_list
+                        .add_line([x, y - 0.4 * r], [x, y + 0.2 * r], black)
+                        .thickness(2.0)
+                        .build();
+                    draw_list
+                        .add_circle([x, y + 0.5 * r], 1.0, black)
+                        .filled(true)
+                        .build();
+                }
+                NodeBadge::Running => {
+                    draw_list
+                        .add_triangle(
+                            [x - 0.7 * r, y - r],
+                            [x + r, y],
+                            [x - 0.7 * r, y + r],
+                            ImColor::from(BADGE_RUNNING),
+                        )
+                        .filled(true)
+                        .build();
+                }
+                NodeBadge::Breakpoint => {
+                    draw_list
+                        .add_circle(center, r, ImColor::from(BADGE_BREAKPOINT))
+                        .filled(true)
+                        .build();
+                }
+            }
+            center[0] -= 2.0 * BADGE_RADIUS + BADGE_SPACING;
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{sys, ColorStyle, ColorToken, EditorContext, ScopeEditor, StyleFlag};
+use imgui::{sys::ImVec2, ImColor, TextureId, Ui, WindowDrawList};
+
+/// converts between grid space and screen space while the editor is drawn,
+/// see [ScopeEditor::canvas_draw]
+///
+/// imnodes has no zoom, so the transform is only an offset
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GridTransform {
+    /// screen space position of the grid origin
+    offset: [f32; 2],
+}
+
+impl GridTransform {
+    /// screen space position of a point on the grid
+    pub fn to_screen(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        [x + self.offset[0], y + self.offset[1]]
+    }
+
+    /// grid space position of a point on the screen
+    pub fn to_grid(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        [x - self.offset[0], y - self.offset[1]]
+    }
+
+    /// screen space position of the grid origin
+    pub fn origin(&self) -> [f32; 2] {
+        self.offset
+    }
+}
+
+impl ScopeEditor<'_> {
+    /// transform for the current panning of this editor
+    pub fn grid_transform(&self) -> GridTransform {
+        let mut panning = sys::ImVec2 { x: 0.0, y: 0.0 };
+        unsafe { sys::imnodes_EditorContextGetPanning(&mut panning as _) };
+        GridTransform {
+            offset: [
+                self.canvas_origin[0] + panning.x,
+                self.canvas_origin[1] + panning.y,
+            ],
+        }
+    }
+
+    /// draw on the canvas in grid space, e.g. annotations, heatmaps or debug geometry which
+    /// moves with the nodes when the editor is panned
+    ///
+    /// the draw list is the one of the canvas, so everything is clipped to it and drawn
+    /// on top of the grid
+    pub fn canvas_draw<F, R>(&mut self, ui: &Ui, f: F) -> R
+    where
+        F: FnOnce(&WindowDrawList<'_>, GridTransform) -> R,
+    {
+        let transform = self.grid_transform();
+        let draw_list = ui.get_window_draw_list();
+        f(&draw_list, transform)
+    }
+}
+
+/// texture drawn behind the grid which moves with the canvas when it is panned,
+/// e.g. a floor plan on which the nodes are placed, see [EditorContext::set_background_image]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BackgroundImage {
+    /// texture registered with the renderer
+    pub texture: TextureId,
+    /// size of the image in grid space
+    pub size: [f32; 2],
+    /// grid space position of the top left corner, of one of the tiles if `tiled` is set
+    pub position: [f32; 2],
+    /// repeat the image to fill the whole canvas
+    pub tiled: bool,
+    /// multiplied with the colors of the texture, e.g. to fade it out as a watermark
+    pub tint: ImColor,
+}
+
+impl BackgroundImage {
+    /// image at the grid origin, drawn once without tint
+    pub fn new(texture: TextureId, size: [f32; 2]) -> Self {
+        Self {
+            texture,
+            size,
+            position: [0.0, 0.0],
+            tiled: false,
+            tint: ImColor::from([1.0, 1.0, 1.0, 1.0]),
+        }
+    }
+}
+
+/// how the grid of an editor is drawn, see [EditorContext::set_grid_style]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum GridStyle {
+    /// lines drawn by imnodes, they can be turned off with [StyleFlag::GridLines]
+    #[default]
+    Lines,
+    /// a dot where the grid lines would cross
+    Dots,
+    /// a small cross where the grid lines would cross
+    Crosses,
+}
+
+/// radius of the [GridStyle::Dots]
+const GRID_DOT_RADIUS: f32 = 1.5;
+
+/// half the length of the arms of the [GridStyle::Crosses]
+const GRID_CROSS_SIZE: f32 = 3.0;
+
+impl EditorContext {
+    /// draw the grid of this editor as lines, dots or crosses
+    ///
+    /// dots and crosses use [ColorStyle::GridLine] and [StyleVar::GridSpacing](crate::StyleVar)
+    /// of the style and are hidden together with the lines by [StyleFlag::GridLines]
+    pub fn set_grid_style(&self, grid: GridStyle) -> &Self {
+        self.grid_style.set(grid);
+        self
+    }
+
+    /// draw an image behind the grid of this editor, `None` removes it
+    ///
+    /// the grid background color is not drawn while there is an image,
+    /// the grid lines are still drawn on top
+    pub fn set_background_image(&self, image: Option<BackgroundImage>) -> &Self {
+        self.background_image.set(image);
+        self
+    }
+}
+
+/// style changes which hide what imnodes would draw over the custom background,
+/// they have to be reverted after BeginNodeEditor
+pub(crate) struct CustomBackground {
+    transparent: ColorToken,
+    grid_lines: bool,
+}
+
+impl CustomBackground {
+    pub(crate) fn end(self, context: &EditorContext) {
+        self.transparent.pop();
+        context.set_style_flag(StyleFlag::GridLines, self.grid_lines);
+    }
+}
+
+/// draw the custom background of the current editor into the current window,
+/// `None` if imnodes draws the whole background itself
+pub(crate) fn draw_background(context: &EditorContext) -> Option<CustomBackground> {
+    let image = context.background_image.get();
+    let grid = context.grid_style.get();
+    if image.is_none() && grid == GridStyle::Lines {
+        return None;
+    }
+
+    let origin = context.canvas_origin.get();
+    let size = context.canvas_size.get();
+    let max = [origin[0] + size[0], origin[1] + size[1]];
+    let panning = context.get_panning();
+    let grid_origin = [origin[0] + panning.x, origin[1] + panning.y];
+    let style = *context.get_style();
+    let grid_lines = style.flags & StyleFlag::GridLines as u32 != 0;
+
+    let draw_list = unsafe { imgui::sys::igGetWindowDrawList() };
+    unsafe {
+        imgui::sys::ImDrawList_PushClipRect(draw_list, origin.into(), max.into(), true);
+    }
+    match image {
+        Some(image) => draw_image(draw_list, &image, grid_origin, (origin, max)),
+        None => unsafe {
+            imgui::sys::ImDrawList_AddRectFilled(
+                draw_list,
+                origin.into(),
+                max.into(),
+                style.colors[ColorStyle::GridBackground as usize],
+                0.0,
+                0,
+            )
+        },
+    }
+    if grid != GridStyle::Lines && grid_lines {
+        draw_grid_marks(
+            draw_list,
+            grid,
+            style.grid_spacing,
+            style.colors[ColorStyle::GridLine as usize],
+            grid_origin,
+            (origin, max),
+        );
+    }
+    unsafe { imgui::sys::ImDrawList_PopClipRect(draw_list) };
+
+    let transparent = ColorStyle::GridBackground.push([0.0, 0.0, 0.0, 0.0]);
+    if grid != GridStyle::Lines {
+        context.set_style_flag(StyleFlag::GridLines, false);
+    }
+    Some(CustomBackground {
+        transparent,
+        grid_lines,
+    })
+}
+
+/// dots or crosses where the grid lines would cross, the rect is the canvas like for
+/// [draw_image]
+fn draw_grid_marks(
+    draw_list: *mut imgui::sys::ImDrawList,
+    grid: GridStyle,
+    spacing: f32,
+    color: u32,
+    grid_origin: [f32; 2],
+    (min, max): ([f32; 2], [f32; 2]),
+) {
+    if spacing <= 0.0 {
+        return;
+    }
+    // like the lines of imnodes, start at the first grid line inside the canvas
+    let first_x = min[0] + (grid_origin[0] - min[0]).rem_euclid(spacing);
+    let first_y = min[1] + (grid_origin[1] - min[1]).rem_euclid(spacing);
+    let mut y = first_y;
+    while y < max[1] {
+        let mut x = first_x;
+        while x < max[0] {
+            match grid {
+                GridStyle::Dots => unsafe {
+                    imgui::sys::ImDrawList_AddCircleFilled(
+                        draw_list,
+                        ImVec2::new(x, y),
+                        GRID_DOT_RADIUS,
+                        color,
+                        4,
+                    )
+                },
+                GridStyle::Crosses => {
+                    let lines = [
+                        ([x - GRID_CROSS_SIZE, y], [x + GRID_CROSS_SIZE, y]),
+                        ([x, y - GRID_CROSS_SIZE], [x, y + GRID_CROSS_SIZE]),
+                    ];
+                    for (start, end) in lines.iter() {
+                        unsafe {
+                            imgui::sys::ImDrawList_AddLine(
+                                draw_list,
+                                (*start).into(),
+                                (*end).into(),
+                                color,
+                                1.0,
+                            )
+                        };
+                    }
+                }
+                GridStyle::Lines => {}
+            }
+            x += spacing;
+        }
+        y += spacing;
+    }
+}
+
+/// `grid_origin` is the screen space position of the grid origin,
+/// `(min, max)` the screen space rect of the canvas
+fn draw_image(
+    draw_list: *mut imgui::sys::ImDrawList,
+    image: &BackgroundImage,
+    grid_origin: [f32; 2],
+    (min, max): ([f32; 2], [f32; 2]),
+) {
+    let [width, height] = image.size;
+    if width <= 0.0 || height <= 0.0 {
+        return;
+    }
+    let start = [
+        grid_origin[0] + image.position[0],
+        grid_origin[1] + image.position[1],
+    ];
+    // first and last tile which touch the canvas
+    let tiles = |start: f32, min: f32, max: f32, size: f32| {
+        if image.tiled {
+            ((min - start) / size).floor() as i32..=((max - start) / size).floor() as i32
+        } else {
+            0..=0
+        }
+    };
+    let columns = tiles(start[0], min[0], max[0], width);
+    let rows = tiles(start[1], min[1], max[1], height);
+    let tint: u32 = image.tint.into();
+    for row in rows {
+        for column in columns.clone() {
+            let x = start[0] + column as f32 * width;
+            let y = start[1] + row as f32 * height;
+            unsafe {
+                imgui::sys::ImDrawList_AddImage(
+                    draw_list,
+                    image.texture.id() as _,
+                    ImVec2::new(x, y),
+                    ImVec2::new(x + width, y + height),
+                    ImVec2::new(0.0, 0.0),
+                    ImVec2::new(1.0, 1.0),
+                    tint,
+                )
+            };
+        }
+    }
+}
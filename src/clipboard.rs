@@ -0,0 +1,199 @@
+/*!
+Copy/ paste of nodes in a [Graph].
+
+[Clipboard::copy] takes the selected nodes, the links between them and their positions
+relative to each other. [Clipboard::paste] inserts them again with fresh ids.
+With the `serde` feature the clipboard can be serialized, e.g. to paste into another process.
+*/
+
+use crate::{
+    graph::{Graph, Link, Node},
+    CoordinateSystem, EditorContext, NodeId,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// snapshot of some nodes of a [Graph]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Clipboard<N> {
+    /// nodes with their grid space position relative to the top left most node
+    nodes: Vec<(Node<N>, [f32; 2])>,
+    /// links which start and end at one of the nodes
+    links: Vec<Link>,
+}
+
+impl<N: Clone> Clipboard<N> {
+    /// copy the nodes, usually [ScopeNone::selected_nodes](crate::ScopeNone::selected_nodes),
+    /// ids which are not part of the graph are skipped
+    pub fn copy(graph: &Graph<N>, context: &EditorContext, nodes: &[NodeId]) -> Self {
+        let _previous = context.make_current();
+
+        let mut copied: Vec<(Node<N>, [f32; 2])> = nodes
+            .iter()
+            .filter_map(|id| graph.node(*id))
+            .map(|node| {
+                let pos = node.id.get_position(CoordinateSystem::GridSpace);
+                (node.clone(), [pos.x, pos.y])
+            })
+            .collect();
+
+        let min_x = copied.iter().map(|(_, [x, _])| *x).fold(f32::MAX, f32::min);
+        let min_y = copied.iter().map(|(_, [_, y])| *y).fold(f32::MAX, f32::min);
+        for (_, [x, y]) in copied.iter_mut() {
+            *x -= min_x;
+            *y -= min_y;
+        }
+
+        let links = graph
+            .links()
+            .iter()
+            .filter(|link| {
+                copied
+                    .iter()
+                    .any(|(node, _)| node.outputs.iter().any(|pin| pin.id == link.start))
+                    && copied
+                        .iter()
+                        .any(|(node, _)| node.inputs.iter().any(|pin| pin.id == link.end))
+            })
+            .copied()
+            .collect();
+
+        Self {
+            nodes: copied,
+            links,
+        }
+    }
+
+    /// nothing was copied
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// insert the copied nodes and links with fresh ids,
+    /// the top left most node is placed at `position`
+    ///
+    /// returns the ids of the new nodes, e.g. to select them.
+    /// Links to pins which are not part of the copied nodes are skipped
+    pub fn paste(
+        &self,
+        graph: &mut Graph<N>,
+        context: &EditorContext,
+        position: [f32; 2],
+        coordinate_system: CoordinateSystem,
+    ) -> Vec<NodeId> {
+        let _previous = context.make_current();
+
+        let pasted = self.insert(graph);
+        for (id, (_, [x, y])) in pasted.iter().zip(&self.nodes) {
+            id.set_position(position[0] + x, position[1] + y, coordinate_system);
+        }
+        pasted
+    }
+
+    /// the graph part of [Clipboard::paste], the nodes are copied as a whole
+    /// so pin capacities, [Node::reroute] and [Node::collapsed] are kept
+    fn insert(&self, graph: &mut Graph<N>) -> Vec<NodeId> {
+        let mut inputs = HashMap::new();
+        let mut outputs = HashMap::new();
+        let mut pasted = vec![];
+
+        for (node, _) in &self.nodes {
+            let id = graph.insert_copy(node);
+            let copy = graph.node(id).expect("node was just inserted");
+            for (pin, new) in node.inputs.iter().zip(&copy.inputs) {
+                inputs.insert(pin.id, new.id);
+            }
+            for (pin, new) in node.outputs.iter().zip(&copy.outputs) {
+                outputs.insert(pin.id, new.id);
+            }
+            pasted.push(id);
+        }
+
+        // an edited or foreign clipboard may have links to pins which were not copied
+        for link in &self.links {
+            if let (Some(start), Some(end)) = (outputs.get(&link.start), inputs.get(&link.end)) {
+                graph.add_link(*start, *end);
+            }
+        }
+
+        pasted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::Capacity, PinShape};
+
+    /// clipboard with all nodes of the graph at the origin
+    fn copy_all(graph: &Graph<()>) -> Clipboard<()> {
+        Clipboard {
+            nodes: graph
+                .nodes()
+                .iter()
+                .map(|node| (node.clone(), [0.0, 0.0]))
+                .collect(),
+            links: graph.links().to_vec(),
+        }
+    }
+
+    #[test]
+    fn pasted_pins_keep_their_capacity() {
+        let mut graph: Graph<()> = Graph::new();
+        let source = graph.add_node("source", ());
+        let value = graph.add_output(source, "value", PinShape::Circle);
+        let sink = graph.add_node("sink", ());
+        let input = graph.add_input(sink, "input", PinShape::Circle);
+        graph.set_input_capacity(input, Capacity::Replace(1));
+        graph.add_link(value, input);
+
+        let pasted = copy_all(&graph).insert(&mut graph);
+        assert_eq!(pasted.len(), 2);
+        assert!(!pasted.contains(&source) && !pasted.contains(&sink));
+
+        let pasted_source = graph.node(pasted[0]).unwrap();
+        let pasted_sink = graph.node(pasted[1]).unwrap();
+        let (start, end) = (pasted_source.outputs[0].id, pasted_sink.inputs[0].id);
+        assert_ne!(end, input);
+        assert_eq!(pasted_sink.inputs[0].capacity, Capacity::Replace(1));
+        assert!(graph
+            .links()
+            .iter()
+            .any(|link| link.start == start && link.end == end));
+        assert_eq!(graph.links().len(), 2);
+    }
+
+    #[test]
+    fn pasted_reroute_stays_a_reroute() {
+        let mut graph: Graph<()> = Graph::new();
+        let source = graph.add_node("source", ());
+        let value = graph.add_output(source, "value", PinShape::Circle);
+        let sink = graph.add_node("sink", ());
+        let input = graph.add_input(sink, "input", PinShape::Circle);
+        let link = graph.add_link(value, input);
+        let reroute = graph.insert_reroute(link, ()).unwrap();
+        let clipboard = Clipboard {
+            nodes: vec![(graph.node(reroute).unwrap().clone(), [0.0, 0.0])],
+            links: vec![],
+        };
+
+        let pasted = clipboard.insert(&mut graph);
+        let node = graph.node(pasted[0]).unwrap();
+        assert!(node.reroute);
+        assert_eq!((node.inputs.len(), node.outputs.len()), (1, 1));
+    }
+
+    #[test]
+    fn pasted_collapsed_node_stays_collapsed() {
+        let mut graph: Graph<()> = Graph::new();
+        let node = graph.add_node("collapsed", ());
+        graph.node_mut(node).unwrap().collapsed = true;
+        graph.add_node("expanded", ());
+
+        let pasted = copy_all(&graph).insert(&mut graph);
+        assert!(graph.node(pasted[0]).unwrap().collapsed);
+        assert!(!graph.node(pasted[1]).unwrap().collapsed);
+    }
+}
@@ -2,19 +2,80 @@
 // sense here too
 
 use imnodes_sys as sys;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    ptr,
+};
+
+thread_local! {
+    /// imnodes has no getter for the current editor so it is tracked here
+    static CURRENT_EDITOR: Cell<*mut sys::EditorContext> = const { Cell::new(ptr::null_mut()) };
+    /// editors which are not freed yet, a freed editor must never become current again
+    static LIVE_EDITORS: RefCell<Vec<*mut sys::EditorContext>> = const { RefCell::new(Vec::new()) };
+}
+
+fn set_current_editor(raw: *mut sys::EditorContext) {
+    CURRENT_EDITOR.with(|current| current.set(raw));
+    unsafe { sys::imnodes_EditorContextSet(raw) };
+}
+
+fn is_live_editor(raw: *mut sys::EditorContext) -> bool {
+    LIVE_EDITORS.with(|live| live.borrow().contains(&raw))
+}
 
 /// needs to be unique for each editor
+#[derive(Debug)]
 pub struct EditorContext {
-    raw: *mut sys::EditorContext,
+    pub(crate) raw: *mut sys::EditorContext,
+    /// selection seen by the last [ScopeNone::events](crate::ScopeNone::events)
+    pub(crate) last_selection: RefCell<(Vec<crate::NodeId>, Vec<crate::LinkId>)>,
+    /// screen space position of the top left corner of the canvas in the last frame
+    pub(crate) canvas_origin: Cell<[f32; 2]>,
+    /// size of the canvas in the last frame
+    pub(crate) canvas_size: Cell<[f32; 2]>,
+    /// see [EditorContext::set_background_image](crate::EditorContext::set_background_image)
+    pub(crate) background_image: Cell<Option<crate::BackgroundImage>>,
+    /// see [EditorContext::set_grid_style](crate::EditorContext::set_grid_style)
+    pub(crate) grid_style: Cell<crate::GridStyle>,
+    /// pin from which the user is dragging a new link, updated in EndNodeEditor
+    pub(crate) pending_link: Cell<Option<crate::PinId>>,
+    /// see [EditorContext::animate_to_node](crate::EditorContext::animate_to_node)
+    pub(crate) pan_animation: Cell<Option<crate::helpers::PanAnimation>>,
+    /// grid space positions of the selected nodes seen by the last
+    /// [ScopeNone::moved_nodes](crate::ScopeNone::moved_nodes)
+    pub(crate) node_positions: RefCell<HashMap<crate::NodeId, [f32; 2]>>,
+    /// pins marked with [ScopeNode::set_display_only](crate::ScopeNode::set_display_only)
+    /// in the last frame
+    pub(crate) display_only_pins: RefCell<HashSet<crate::PinId>>,
+    /// nodes added with [ScopeEditor::add_disabled_node](crate::ScopeEditor::add_disabled_node)
+    /// in the last frame
+    pub(crate) disabled_nodes: RefCell<HashSet<crate::NodeId>>,
 }
 
 impl EditorContext {
     /// use this context now
+    ///
+    /// the previously current editor is not restored afterwards, see [EditorContextGuard] for that
     pub fn set_as_current_editor(&self) -> &Self {
-        unsafe { sys::imnodes_EditorContextSet(self.raw) };
+        set_current_editor(self.raw);
         self
     }
 
+    /// use this context now and get a token which switches back to the previously current
+    /// editor when it is dropped
+    ///
+    /// unlike [EditorContextGuard] the token does not borrow `self`, so it can be kept around
+    /// while the context is passed on mutably, e.g. to [editor](crate::editor)
+    #[must_use = "dropping the token immediately restores the previous editor"]
+    pub fn make_current(&self) -> PreviousContext {
+        let previous = PreviousContext {
+            raw: CURRENT_EDITOR.with(|current| current.get()),
+        };
+        self.set_as_current_editor();
+        previous
+    }
+
     /// generate Singleton IdentifierGenerator
     pub fn new_identifier_generator(&self) -> crate::IdentifierGenerator {
         crate::IdentifierGenerator::new()
@@ -25,16 +86,83 @@ impl EditorContext {
     pub fn get_style(&self) -> &mut sys::Style {
         unsafe { &mut *(sys::imnodes_GetStyle() as *mut sys::Style) }
     }
+
+    /// the underlying imnodes editor context, for features which are not wrapped yet
+    /// (the functions are available with the `include_low_level_bindings` feature in
+    /// `imnodes::internal`)
+    ///
+    /// # Safety
+    ///
+    /// the pointer is freed when `self` is dropped and must not be passed to
+    /// `imnodes_EditorContextFree` or `imnodes_EditorContextSet`, the latter would get the
+    /// current editor out of sync with [EditorContextGuard]
+    pub unsafe fn raw(&self) -> *mut sys::EditorContext {
+        self.raw
+    }
+
+    /// GetStyle
+    /// the style of the current editor as raw pointer, e.g. to write fields directly
+    ///
+    /// # Safety
+    ///
+    /// the pointer is only valid as long as the imnodes [Context] is alive and must not be
+    /// written to between BeginNodeEditor and EndNodeEditor
+    pub unsafe fn raw_style(&self) -> *mut sys::Style {
+        sys::imnodes_GetStyle()
+    }
 }
 
 impl Drop for EditorContext {
     fn drop(&mut self) {
+        LIVE_EDITORS.with(|live| live.borrow_mut().retain(|raw| *raw != self.raw));
+        CURRENT_EDITOR.with(|current| {
+            if current.get() == self.raw {
+                current.set(ptr::null_mut());
+            }
+        });
         unsafe {
             sys::imnodes_EditorContextFree(self.raw);
         }
     }
 }
 
+/// the editor which was current before [EditorContext::make_current] was called,
+/// it becomes current again when this is dropped
+#[derive(Debug)]
+pub struct PreviousContext {
+    raw: *mut sys::EditorContext,
+}
+
+impl Drop for PreviousContext {
+    fn drop(&mut self) {
+        // the previous editor might have been freed in the meantime
+        if is_live_editor(self.raw) {
+            set_current_editor(self.raw);
+        }
+    }
+}
+
+/// makes an editor current for as long as the guard lives
+/// and switches back to the previously current editor when it is dropped
+///
+/// [editor](crate::editor) uses this so rendering several editors (e.g. in tabs) does not
+/// require calling [EditorContext::set_as_current_editor] by hand
+#[derive(Debug)]
+pub struct EditorContextGuard<'a> {
+    _previous: PreviousContext,
+    pub(crate) context: &'a EditorContext,
+}
+
+impl<'a> EditorContextGuard<'a> {
+    /// make `context` the current editor
+    pub fn new(context: &'a EditorContext) -> Self {
+        Self {
+            _previous: context.make_current(),
+            context,
+        }
+    }
+}
+
 /// imnodes_Initialize
 pub struct Context {}
 
@@ -48,8 +176,20 @@ impl Context {
 
     /// created the context for one editor/ grid
     pub fn create_editor(&self) -> EditorContext {
+        let raw = unsafe { sys::imnodes_EditorContextCreate() };
+        LIVE_EDITORS.with(|live| live.borrow_mut().push(raw));
         EditorContext {
-            raw: unsafe { sys::imnodes_EditorContextCreate() },
+            raw,
+            last_selection: RefCell::default(),
+            canvas_origin: Cell::default(),
+            canvas_size: Cell::default(),
+            background_image: Cell::default(),
+            grid_style: Cell::default(),
+            pending_link: Cell::default(),
+            pan_animation: Cell::default(),
+            node_positions: RefCell::default(),
+            display_only_pins: RefCell::default(),
+            disabled_nodes: RefCell::default(),
         }
     }
 }
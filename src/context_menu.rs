@@ -0,0 +1,120 @@
+use crate::{sys, LinkId, NodeId, PinId, ScopeNone};
+use imgui::{im_str, ImStr, MouseButton, Ui};
+
+/// what the user right clicked on, see [ContextMenu]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum ContextTarget {
+    /// empty space of the editor
+    Canvas,
+    /// IsNodeHovered
+    Node(NodeId),
+    /// IsLinkHovered
+    Link(LinkId),
+    /// IsPinHovered
+    Pin(PinId),
+}
+
+impl ContextTarget {
+    fn popup_id(self) -> &'static ImStr {
+        match self {
+            ContextTarget::Canvas => im_str!("imnodes_canvas_context_menu"),
+            ContextTarget::Node(_) => im_str!("imnodes_node_context_menu"),
+            ContextTarget::Link(_) => im_str!("imnodes_link_context_menu"),
+            ContextTarget::Pin(_) => im_str!("imnodes_pin_context_menu"),
+        }
+    }
+}
+
+/// opens a different popup depending on what was right clicked
+/// and remembers the grid space position of the click while the popup is open
+///
+/// ```ignore
+/// let (scope, ()) = editor(&ui, &mut context, |editor| { ... });
+/// menu.update(&ui, &scope);
+/// menu.canvas(&ui, |position| {
+///     if MenuItem::new(im_str!("add node")).build(&ui) {
+///         let id = graph.add_node("node", ());
+///         id.set_position(position[0], position[1], CoordinateSystem::GridSpace);
+///     }
+/// });
+/// menu.node(&ui, |id, _| {
+///     if MenuItem::new(im_str!("delete")).build(&ui) {
+///         graph.remove_node(id);
+///     }
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct ContextMenu {
+    target: Option<ContextTarget>,
+    position: [f32; 2],
+}
+
+impl ContextMenu {
+    /// nothing clicked yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// check for a right click on the editor and open the popup for the item under the mouse,
+    /// has to be called every frame after [editor](crate::editor)
+    ///
+    /// pins take precedence over links, links over nodes and nodes over the canvas
+    pub fn update(&mut self, ui: &Ui, scope: &ScopeNone) -> Option<ContextTarget> {
+        if !ui.is_mouse_clicked(MouseButton::Right) {
+            return None;
+        }
+
+        let target = if let Some(pin) = scope.get_hovered_pin() {
+            ContextTarget::Pin(pin)
+        } else if let Some(link) = scope.get_hovered_link() {
+            ContextTarget::Link(link)
+        } else if let Some(node) = scope.get_hovered_node() {
+            ContextTarget::Node(node)
+        } else if unsafe { sys::imnodes_IsEditorHovered() } {
+            ContextTarget::Canvas
+        } else {
+            return None;
+        };
+
+        self.target = Some(target);
+        self.position = scope.guard.context.screen_to_grid(ui.io().mouse_pos);
+        ui.open_popup(target.popup_id());
+        Some(target)
+    }
+
+    /// item of the last right click
+    pub fn target(&self) -> Option<ContextTarget> {
+        self.target
+    }
+
+    /// grid space position of the last right click, e.g. to spawn a node there
+    pub fn position(&self) -> [f32; 2] {
+        self.position
+    }
+
+    /// content of the popup for a click on the empty canvas
+    pub fn canvas<F: FnOnce([f32; 2])>(&self, ui: &Ui, f: F) {
+        ui.popup(ContextTarget::Canvas.popup_id(), || f(self.position));
+    }
+
+    /// content of the popup for a click on a node
+    pub fn node<F: FnOnce(NodeId, [f32; 2])>(&self, ui: &Ui, f: F) {
+        if let Some(ContextTarget::Node(id)) = self.target {
+            ui.popup(ContextTarget::Node(id).popup_id(), || f(id, self.position));
+        }
+    }
+
+    /// content of the popup for a click on a link
+    pub fn link<F: FnOnce(LinkId, [f32; 2])>(&self, ui: &Ui, f: F) {
+        if let Some(ContextTarget::Link(id)) = self.target {
+            ui.popup(ContextTarget::Link(id).popup_id(), || f(id, self.position));
+        }
+    }
+
+    /// content of the popup for a click on a pin
+    pub fn pin<F: FnOnce(PinId, [f32; 2])>(&self, ui: &Ui, f: F) {
+        if let Some(ContextTarget::Pin(id)) = self.target {
+            ui.popup(ContextTarget::Pin(id).popup_id(), || f(id, self.position));
+        }
+    }
+}
@@ -0,0 +1,102 @@
+/*!
+JSON documents with everything needed to show a [Graph] again, so applications built on this
+crate can exchange graphs.
+
+```json
+{
+  "version": 1,
+  "graph": {
+    "nodes": [{ "id": { "id": 0 }, "title": "add", "inputs": [...], "outputs": [...], "data": ... }],
+    "links": [{ "id": { "id": 3 }, "start": { "id": 2 }, "end": { "id": 5 } }],
+    "id_gen": { ... }
+  },
+  "layout": { "panning": [0.0, 0.0], "positions": [[{ "id": 0 }, [40.0, 80.0]]] },
+  "theme": {
+    "colors": { "NodeBackground": [0.2, 0.2, 0.2, 1.0], ... },
+    "vars": { "GridSpacing": 32.0, ... },
+    "flags": ["NodeOutline", "GridLines"]
+  }
+}
+```
+
+`graph` is the serde representation of [Graph] with the node data `N`, positions are in grid
+space and colors are `[r, g, b, a]` between 0 and 1. `theme` is optional.
+
+```ignore
+let json = Document::capture(graph, &context, true).save_json()?;
+let document: Document<MyData> = Document::load_json(&json)?;
+document.restore(&context);
+```
+*/
+
+use crate::{
+    graph::{EditorLayout, Graph},
+    EditorContext, Theme,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// version of the format written by [Document::save_json],
+/// [Document::load_json] refuses documents with a higher version
+pub const FORMAT_VERSION: u32 = 1;
+
+/// graph together with the layout and optionally the theme of its editor
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Document<N> {
+    /// [FORMAT_VERSION] of the writer
+    pub version: u32,
+    /// nodes, pins and links
+    pub graph: Graph<N>,
+    /// panning and node positions
+    pub layout: EditorLayout,
+    /// look of the editor
+    #[serde(default)]
+    pub theme: Option<Theme>,
+}
+
+impl<N> Document<N> {
+    /// document with the current layout of `graph` in `context`,
+    /// and the current style if `with_theme` is set
+    pub fn capture(graph: Graph<N>, context: &EditorContext, with_theme: bool) -> Self {
+        let layout = graph.capture_layout(context);
+        Self {
+            version: FORMAT_VERSION,
+            graph,
+            layout,
+            theme: if with_theme {
+                Some(Theme::capture(context))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// move the nodes and the panning of `context` to the layout and apply the theme if
+    /// there is one
+    pub fn restore(&self, context: &EditorContext) {
+        self.graph.apply_layout(context, &self.layout);
+        if let Some(theme) = &self.theme {
+            theme.apply(context);
+        }
+    }
+}
+
+impl<N: Serialize> Document<N> {
+    /// pretty printed json
+    pub fn save_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl<N: DeserializeOwned> Document<N> {
+    /// parse a document written by [Document::save_json]
+    pub fn load_json(json: &str) -> Result<Self, serde_json::Error> {
+        let document: Self = serde_json::from_str(json)?;
+        if document.version > FORMAT_VERSION {
+            return Err(serde::de::Error::custom(format!(
+                "document version {} is newer than the supported version {}",
+                document.version, FORMAT_VERSION
+            )));
+        }
+        Ok(document)
+    }
+}
@@ -0,0 +1,147 @@
+/*!
+Export of a [Graph] in the DOT language of [Graphviz](https://graphviz.org), e.g. for
+documentation or to analyze it with other tools.
+
+Every node becomes a record with one field per pin, so the links start and end at their pins.
+
+```ignore
+std::fs::write("graph.dot", graph.to_dot())?;
+// `neato -n` keeps the positions of the editor
+std::fs::write("layout.dot", graph.to_dot_with_layout(&graph.capture_layout(&context)))?;
+```
+*/
+
+use crate::{
+    graph::{EditorLayout, Graph, Node, Pin},
+    NodeId, PinId,
+};
+use std::{collections::HashMap, fmt::Write};
+
+impl<N> Graph<N> {
+    /// DOT source with the titles and pin labels of all nodes and all links
+    pub fn to_dot(&self) -> String {
+        self.write_dot(&HashMap::new())
+    }
+
+    /// like [Graph::to_dot] with the grid space positions of the layout as `pos` of the nodes,
+    /// the y axis is flipped because it points up in Graphviz
+    pub fn to_dot_with_layout(&self, layout: &EditorLayout) -> String {
+        let positions = layout.positions.iter().copied().collect();
+        self.write_dot(&positions)
+    }
+
+    fn write_dot(&self, positions: &HashMap<NodeId, [f32; 2]>) -> String {
+        let mut dot = String::from("digraph {\n    rankdir=LR;\n    node [shape=record];\n");
+        let mut owners: HashMap<PinId, NodeId> = HashMap::new();
+        for node in self.nodes() {
+            owners.extend(node.inputs.iter().map(|pin| (pin.id.into(), node.id)));
+            owners.extend(node.outputs.iter().map(|pin| (pin.id.into(), node.id)));
+
+            let id: i32 = node.id.into();
+            write!(dot, "    n{} [label=\"{}\"", id, record_label(node)).unwrap();
+            if let Some([x, y]) = positions.get(&node.id) {
+                write!(dot, ", pos=\"{},{}!\"", x, -y).unwrap();
+            }
+            dot.push_str("];\n");
+        }
+        for link in self.links() {
+            let start = owners.get(&link.start.into());
+            let end = owners.get(&link.end.into());
+            if let (Some(start), Some(end)) = (start, end) {
+                let (start, end): (i32, i32) = ((*start).into(), (*end).into());
+                let (start_pin, end_pin): (i32, i32) = (link.start.into(), link.end.into());
+                writeln!(
+                    dot,
+                    "    n{}:p{}:e -> n{}:p{}:w;",
+                    start, start_pin, end, end_pin
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// inputs, title and outputs next to each other, the pins of a side below each other
+///
+/// with `rankdir=LR` the fields of a record are stacked, so the braces turn them around
+fn record_label<N>(node: &Node<N>) -> String {
+    let mut fields = vec![];
+    if !node.inputs.is_empty() {
+        fields.push(pin_fields(&node.inputs));
+    }
+    fields.push(escape(&node.title));
+    if !node.outputs.is_empty() {
+        fields.push(pin_fields(&node.outputs));
+    }
+    format!("{{{}}}", fields.join("|"))
+}
+
+fn pin_fields<Id: Copy + Into<i32>>(pins: &[Pin<Id>]) -> String {
+    let fields: Vec<String> = pins
+        .iter()
+        .map(|pin| {
+            let id: i32 = pin.id.into();
+            format!("<p{}> {}", id, escape(&pin.label))
+        })
+        .collect();
+    format!("{{{}}}", fields.join("|"))
+}
+
+/// characters which have a meaning in record labels or strings,
+/// line breaks become the `\n` escape sequence of Graphviz
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            '\\' | '"' | '{' | '}' | '|' | '<' | '>' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PinShape;
+
+    #[test]
+    fn writes_records_and_links_between_pins() {
+        let mut graph: Graph<()> = Graph::new();
+        let source = graph.add_node("source", ());
+        let value = graph.add_output(source, "value", PinShape::Circle);
+        let sink = graph.add_node("sink", ());
+        let input = graph.add_input(sink, "input", PinShape::Circle);
+        graph.add_link(value, input);
+        let layout = EditorLayout {
+            panning: [0.0, 0.0],
+            positions: vec![(sink, [100.0, 50.0])],
+        };
+
+        assert_eq!(
+            graph.to_dot_with_layout(&layout),
+            "digraph {
+    rankdir=LR;
+    node [shape=record];
+    n0 [label=\"{source|{<p0> value}}\"];
+    n1 [label=\"{{<p1> input}|sink}\", pos=\"100,-50!\"];
+    n0:p0:e -> n1:p1:w;
+}
+"
+        );
+    }
+
+    #[test]
+    fn escapes_quotes_line_breaks_and_record_syntax() {
+        assert_eq!(escape("say \"hi\""), "say \\\"hi\\\"");
+        assert_eq!(escape("two\nlines\r\n"), "two\\nlines\\n");
+        assert_eq!(escape("{a|<b>} \\"), "\\{a\\|\\<b\\>\\} \\\\");
+    }
+}
@@ -0,0 +1,58 @@
+use crate::ScopeNone;
+use imgui::{ImStr, Ui};
+use std::mem::size_of;
+
+/// make the last imgui item a drag and drop source for `value`,
+/// e.g. a button in a palette next to the editor
+///
+/// `payload_type` may be at most 32 bytes long and `preview` is drawn next to the mouse
+/// while dragging. imgui copies the bytes of `value`, so only use plain data without pointers or ownership.
+pub fn drag_source<T: Copy, F: FnOnce()>(payload_type: &ImStr, value: &T, preview: F) {
+    if unsafe { imgui::sys::igBeginDragDropSource(0) } {
+        unsafe {
+            imgui::sys::igSetDragDropPayload(
+                payload_type.as_ptr(),
+                value as *const T as _,
+                size_of::<T>(),
+                0,
+            )
+        };
+        preview();
+        unsafe { imgui::sys::igEndDragDropSource() };
+    }
+}
+
+impl ScopeNone<'_> {
+    /// value of a payload of `payload_type` which was dropped onto the canvas in this frame
+    /// together with the grid space drop position, e.g. to spawn a node there
+    ///
+    /// the canvas has to be the last imgui item, so call this right after [editor](crate::editor)
+    /// before drawing any other widgets.
+    /// Payloads of a different size than `T` are ignored.
+    pub fn canvas_drop_target<T: Copy>(
+        &self,
+        ui: &Ui,
+        payload_type: &ImStr,
+    ) -> Option<(T, [f32; 2])> {
+        if !unsafe { imgui::sys::igBeginDragDropTarget() } {
+            return None;
+        }
+
+        let payload = unsafe { imgui::sys::igAcceptDragDropPayload(payload_type.as_ptr(), 0) };
+        let value = if payload.is_null() {
+            None
+        } else {
+            let payload = unsafe { &*payload };
+            if payload.DataSize as usize == size_of::<T>() {
+                // the payload buffer is not aligned for T
+                Some(unsafe { std::ptr::read_unaligned(payload.Data as *const T) })
+            } else {
+                None
+            }
+        };
+        unsafe { imgui::sys::igEndDragDropTarget() };
+
+        let position = self.guard.context.screen_to_grid(ui.io().mouse_pos);
+        value.map(|value| (value, position))
+    }
+}
@@ -0,0 +1,300 @@
+/*!
+Evaluation of a [Graph] as a dataflow program.
+
+Nodes are visited in topological order. For every node the closure passed to [evaluate] gets
+the values arriving at its input pins and returns one value per output pin, which is then
+passed along all links starting at that pin.
+
+```ignore
+let values = evaluate(&graph, |node, inputs| match node.data {
+    Op::Constant(value) => vec![value],
+    Op::Add => vec![inputs.iter().flatten().sum()],
+    Op::Print => {
+        println!("{:?}", inputs[0]);
+        vec![]
+    }
+})?;
+```
+*/
+
+use crate::{
+    graph::{Graph, Node},
+    layout::graph_edges,
+    InputPinId, NodeId, OutputPinId,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+};
+
+/// the graph contains a cycle, so there is no order in which the nodes can be evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// the nodes of one cycle in link order, the last node links back to the first,
+    /// a self loop is a cycle of one node
+    pub nodes: Vec<NodeId>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the graph contains a cycle through {} nodes",
+            self.nodes.len()
+        )
+    }
+}
+
+impl std::error::Error for Cycle {}
+
+/// all nodes ordered so that every link points from an earlier to a later node
+pub fn topological_order<N>(graph: &Graph<N>) -> Result<Vec<NodeId>, Cycle> {
+    let mut incoming: HashMap<NodeId, usize> =
+        graph.nodes().iter().map(|node| (node.id, 0)).collect();
+    let mut outgoing: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    let mut predecessors: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    for (from, to) in graph_edges(graph) {
+        *incoming.entry(to).or_default() += 1;
+        outgoing.entry(from).or_default().push(to);
+        predecessors.entry(to).or_default().push(from);
+    }
+
+    // start with the nodes in graph order so the result is deterministic
+    let mut queue: VecDeque<NodeId> = graph
+        .nodes()
+        .iter()
+        .map(|node| node.id)
+        .filter(|id| incoming[id] == 0)
+        .collect();
+    let mut order = Vec::with_capacity(graph.nodes().len());
+    while let Some(id) = queue.pop_front() {
+        order.push(id);
+        for next in outgoing.get(&id).into_iter().flatten() {
+            let count = incoming.get_mut(next).expect("edge to unknown node");
+            *count -= 1;
+            if *count == 0 {
+                queue.push_back(*next);
+            }
+        }
+    }
+
+    if order.len() == graph.nodes().len() {
+        return Ok(order);
+    }
+
+    // every node which was not ordered still waits for another one which was not ordered,
+    // so walking backwards along those links has to run into a cycle
+    let remaining: Vec<NodeId> = graph
+        .nodes()
+        .iter()
+        .map(|node| node.id)
+        .filter(|id| incoming[id] > 0)
+        .collect();
+    let mut path = vec![remaining[0]];
+    let mut seen: HashMap<NodeId, usize> = HashMap::from([(remaining[0], 0)]);
+    loop {
+        let current = path[path.len() - 1];
+        let previous = *predecessors[&current]
+            .iter()
+            .find(|id| incoming[*id] > 0)
+            .expect("node waits for an ordered node");
+        if let Some(&start) = seen.get(&previous) {
+            let mut nodes = path.split_off(start);
+            nodes.reverse();
+            // start at the node which comes first in the graph so the result is deterministic
+            let first = (0..nodes.len())
+                .min_by_key(|&i| remaining.iter().position(|id| *id == nodes[i]))
+                .expect("cycle has nodes");
+            nodes.rotate_left(first);
+            return Err(Cycle { nodes });
+        }
+        seen.insert(previous, path.len());
+        path.push(previous);
+    }
+}
+
+/// check if a new link from the node `from` to the node `to` would close a cycle,
+/// e.g. with the `start_node` and `end_node` of [ScopeNone::links_created](crate::ScopeNone::links_created)
+///
+/// returns the cycle starting at `to` and ending at `from`, a self loop is a cycle of one node
+pub fn would_create_cycle(edges: &[(NodeId, NodeId)], from: NodeId, to: NodeId) -> Option<Cycle> {
+    let mut outgoing: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    for (a, b) in edges {
+        outgoing.entry(*a).or_default().push(*b);
+    }
+
+    // breadth first search from `to` back to `from`, remembering where each node was reached from
+    let mut reached_from: HashMap<NodeId, NodeId> = HashMap::new();
+    let mut queue = VecDeque::from(vec![to]);
+    while let Some(id) = queue.pop_front() {
+        if id == from {
+            let mut nodes = vec![from];
+            let mut current = from;
+            while current != to {
+                current = reached_from[&current];
+                nodes.push(current);
+            }
+            nodes.reverse();
+            return Some(Cycle { nodes });
+        }
+        for next in outgoing.get(&id).into_iter().flatten() {
+            if *next != to && !reached_from.contains_key(next) {
+                reached_from.insert(*next, id);
+                queue.push_back(*next);
+            }
+        }
+    }
+    None
+}
+
+/// values of all pins after [evaluate]
+#[derive(Debug, Clone)]
+pub struct Values<V> {
+    outputs: HashMap<OutputPinId, V>,
+    inputs: HashMap<InputPinId, V>,
+}
+
+impl<V> Values<V> {
+    /// value computed for an output pin
+    pub fn output(&self, pin: OutputPinId) -> Option<&V> {
+        self.outputs.get(&pin)
+    }
+
+    /// value which arrived at an input pin, `None` if nothing is connected to it
+    pub fn input(&self, pin: InputPinId) -> Option<&V> {
+        self.inputs.get(&pin)
+    }
+}
+
+/// run `compute` for every node in [topological_order]
+///
+/// `compute` gets one entry per input pin of the node, `None` for pins without a link.
+/// If several links end at the same input pin the first one in [Graph::links] is used.
+///
+/// panics if `compute` does not return exactly one value per output pin
+pub fn evaluate<N, V, F>(graph: &Graph<N>, mut compute: F) -> Result<Values<V>, Cycle>
+where
+    V: Clone,
+    F: FnMut(&Node<N>, &[Option<V>]) -> Vec<V>,
+{
+    let order = topological_order(graph)?;
+
+    let mut sources: HashMap<InputPinId, OutputPinId> = HashMap::new();
+    for link in graph.links() {
+        sources.entry(link.end).or_insert(link.start);
+    }
+
+    let mut values: Values<V> = Values {
+        outputs: HashMap::new(),
+        inputs: HashMap::new(),
+    };
+    for id in order {
+        let node = graph.node(id).expect("node from topological_order");
+        let inputs: Vec<Option<V>> = node
+            .inputs
+            .iter()
+            .map(|pin| {
+                let value = values.outputs.get(sources.get(&pin.id)?)?.clone();
+                values.inputs.insert(pin.id, value.clone());
+                Some(value)
+            })
+            .collect();
+
+        let outputs = compute(node, &inputs);
+        assert_eq!(
+            outputs.len(),
+            node.outputs.len(),
+            "compute has to return one value per output pin"
+        );
+        for (pin, value) in node.outputs.iter().zip(outputs) {
+            values.outputs.insert(pin.id, value);
+        }
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PinShape;
+
+    /// graph with `count` nodes which have one input and one output each
+    fn nodes(count: usize) -> (Graph<()>, Vec<NodeId>) {
+        let mut graph = Graph::new();
+        let ids = (0..count)
+            .map(|i| {
+                let id = graph.add_node(format!("node {}", i), ());
+                graph.add_input(id, "in", PinShape::Circle);
+                graph.add_output(id, "out", PinShape::Circle);
+                id
+            })
+            .collect();
+        (graph, ids)
+    }
+
+    fn link(graph: &mut Graph<()>, from: NodeId, to: NodeId) {
+        let start = graph.node(from).unwrap().outputs[0].id;
+        let end = graph.node(to).unwrap().inputs[0].id;
+        graph.add_link(start, end);
+    }
+
+    #[test]
+    fn orders_a_dag() {
+        let (mut graph, ids) = nodes(4);
+        // diamond added out of order: 3 -> 1, 3 -> 2, 1 -> 0, 2 -> 0
+        link(&mut graph, ids[1], ids[0]);
+        link(&mut graph, ids[3], ids[1]);
+        link(&mut graph, ids[2], ids[0]);
+        link(&mut graph, ids[3], ids[2]);
+
+        let order = topological_order(&graph).unwrap();
+        assert_eq!(order, vec![ids[3], ids[1], ids[2], ids[0]]);
+    }
+
+    #[test]
+    fn reports_the_nodes_of_a_cycle_in_link_order() {
+        let (mut graph, ids) = nodes(5);
+        // 0 -> 1 -> 2 -> 3 -> 1, and 3 -> 4 only depends on the cycle
+        link(&mut graph, ids[0], ids[1]);
+        link(&mut graph, ids[1], ids[2]);
+        link(&mut graph, ids[2], ids[3]);
+        link(&mut graph, ids[3], ids[1]);
+        link(&mut graph, ids[3], ids[4]);
+
+        let cycle = topological_order(&graph).unwrap_err();
+        assert_eq!(cycle.nodes, vec![ids[1], ids[2], ids[3]]);
+    }
+
+    #[test]
+    fn reports_a_self_loop() {
+        let (mut graph, ids) = nodes(2);
+        link(&mut graph, ids[0], ids[1]);
+        link(&mut graph, ids[1], ids[1]);
+
+        let cycle = topological_order(&graph).unwrap_err();
+        assert_eq!(cycle.nodes, vec![ids[1]]);
+    }
+
+    #[test]
+    fn self_loop_would_create_a_cycle() {
+        let (_, ids) = nodes(1);
+        let cycle = would_create_cycle(&[], ids[0], ids[0]).unwrap();
+        assert_eq!(cycle.nodes, vec![ids[0]]);
+    }
+
+    #[test]
+    fn closing_a_chain_would_create_a_cycle() {
+        let (_, ids) = nodes(3);
+        let edges = [(ids[0], ids[1]), (ids[1], ids[2])];
+        let cycle = would_create_cycle(&edges, ids[2], ids[0]).unwrap();
+        assert_eq!(cycle.nodes, vec![ids[0], ids[1], ids[2]]);
+    }
+
+    #[test]
+    fn parallel_link_would_not_create_a_cycle() {
+        let (_, ids) = nodes(3);
+        let edges = [(ids[0], ids[1]), (ids[1], ids[2])];
+        assert!(would_create_cycle(&edges, ids[0], ids[2]).is_none());
+        assert!(would_create_cycle(&edges, ids[2], ids[1]).is_some());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{
+    sys, AttributeId, CoordinateSystem, EditorContext, ImVec2, Link, LinkId, NodeId, PinId,
+    ScopeEditor, ScopeNone,
+};
+use imgui::{Key, Ui};
+use std::collections::HashMap;
+
+/// everything that can happen in one frame of an editor, see [ScopeNone::events]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// IsLinkCreated
+    LinkCreated(Link),
+    /// IsLinkDestroyed
+    LinkDestroyed(LinkId),
+    /// IsLinkStarted
+    LinkStarted(PinId),
+    /// IsLinkDropped, a new link was dropped without ending at a pin
+    LinkDropped(PinId),
+    /// IsNodeHovered
+    NodeHovered(NodeId),
+    /// IsPinHovered
+    PinHovered(PinId),
+    /// IsLinkHovered
+    LinkHovered(LinkId),
+    /// IsAnyAttributeActive
+    AttributeActive(AttributeId),
+    /// the selected nodes or links differ from the last call to [ScopeNone::events]
+    SelectionChanged {
+        /// all selected nodes
+        nodes: Vec<NodeId>,
+        /// all selected links
+        links: Vec<LinkId>,
+    },
+}
+
+impl ScopeNone<'_> {
+    /// all events of this frame instead of asking for each of them separately
+    ///
+    /// [Event::SelectionChanged] compares with the selection seen the last time this was called
+    /// for the same editor, so call it once per frame
+    pub fn events(&self) -> impl Iterator<Item = Event> {
+        let mut events = vec![];
+
+        if let Some(link) = self.links_created() {
+            events.push(Event::LinkCreated(link));
+        }
+        if let Some(link) = self.link_destroyed() {
+            events.push(Event::LinkDestroyed(link));
+        }
+        if let Some(pin) = self.from_where_link_started() {
+            events.push(Event::LinkStarted(pin));
+        }
+        if let Some(pin) = self.from_where_link_dropped(false) {
+            events.push(Event::LinkDropped(pin));
+        }
+        if let Some(node) = self.get_hovered_node() {
+            events.push(Event::NodeHovered(node));
+        }
+        if let Some(pin) = self.get_hovered_pin() {
+            events.push(Event::PinHovered(pin));
+        }
+        if let Some(link) = self.get_hovered_link() {
+            events.push(Event::LinkHovered(link));
+        }
+        if let Some(attribute) = self.get_active_attribute() {
+            events.push(Event::AttributeActive(attribute));
+        }
+
+        let selection = (self.selected_nodes(), self.selected_links());
+        let mut last_selection = self.guard.context.last_selection.borrow_mut();
+        if *last_selection != selection {
+            events.push(Event::SelectionChanged {
+                nodes: selection.0.clone(),
+                links: selection.1.clone(),
+            });
+            *last_selection = selection;
+        }
+
+        events.into_iter()
+    }
+}
+
+/// selected nodes and links the user wants to delete, see [ScopeNone::deletion_request]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeletionRequest {
+    /// selected nodes
+    pub nodes: Vec<NodeId>,
+    /// selected links
+    pub links: Vec<LinkId>,
+}
+
+impl ScopeNone<'_> {
+    /// the selection if Delete or Backspace was pressed while the mouse is over the editor
+    ///
+    /// nothing is returned while a text field has the keyboard focus or nothing is selected,
+    /// [Graph::delete](crate::graph::Graph::delete) applies the request to a retained graph
+    pub fn deletion_request(&self, ui: &Ui) -> Option<DeletionRequest> {
+        let pressed = ui.is_key_pressed(ui.key_index(Key::Delete))
+            || ui.is_key_pressed(ui.key_index(Key::Backspace));
+        if !pressed || ui.io().want_text_input || !unsafe { sys::imnodes_IsEditorHovered() } {
+            return None;
+        }
+
+        let request = DeletionRequest {
+            nodes: self.selected_nodes(),
+            links: self.selected_links(),
+        };
+        if request.nodes.is_empty() && request.links.is_empty() {
+            None
+        } else {
+            Some(request)
+        }
+    }
+}
+
+/// a new link which the user is dragging from a pin,
+/// see [ScopeNone::pending_link] and [ScopeEditor::pending_link]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PendingLink {
+    /// pin the link was started from
+    pub start: PinId,
+    /// screen space position of the mouse, where the loose end of the link is drawn
+    pub mouse: [f32; 2],
+}
+
+/// remember where a link was started until the mouse is released,
+/// imnodes only reports the start in the frame it happens
+pub(crate) fn track_pending_link(context: &EditorContext) {
+    let mut id: i32 = -1;
+    if unsafe { sys::imnodes_IsLinkStarted(&mut id as _) } {
+        context.pending_link.set(Some(PinId { id }));
+    } else if !unsafe { imgui::sys::igIsMouseDown(imgui::sys::ImGuiMouseButton_Left as _) } {
+        // the link is created or dropped when the mouse is released
+        context.pending_link.set(None);
+    }
+}
+
+impl ScopeNone<'_> {
+    /// the link the user is dragging right now, `None` once it is created or dropped
+    pub fn pending_link(&self, ui: &Ui) -> Option<PendingLink> {
+        self.guard
+            .context
+            .pending_link
+            .get()
+            .filter(|&pin| !self.is_display_only(pin))
+            .map(|start| PendingLink {
+                start,
+                mouse: ui.io().mouse_pos,
+            })
+    }
+}
+
+impl ScopeEditor<'_> {
+    /// the link the user is dragging, as known at the end of the last frame,
+    /// e.g. to highlight the pins it could be connected to while the nodes are drawn
+    pub fn pending_link(&self, ui: &Ui) -> Option<PendingLink> {
+        self.pending_link.map(|start| PendingLink {
+            start,
+            mouse: ui.io().mouse_pos,
+        })
+    }
+}
+
+impl ScopeNone<'_> {
+    /// nodes which moved since the last call for the same editor, with their new grid space position
+    ///
+    /// the user can only drag selected nodes, so only the positions of those are compared
+    /// instead of polling every node. A node is reported from the second frame it is selected,
+    /// nodes moved by the application while they are selected are reported as well.
+    /// Call it once per frame
+    pub fn moved_nodes(&self) -> Vec<(NodeId, ImVec2)> {
+        let mut cached = self.guard.context.node_positions.borrow_mut();
+        let mut positions = HashMap::with_capacity(cached.len());
+        let mut moved = vec![];
+
+        for id in self.selected_nodes() {
+            let position = id.get_position(CoordinateSystem::GridSpace);
+            if matches!(cached.get(&id), Some(&[x, y]) if x != position.x || y != position.y) {
+                moved.push((id, position));
+            }
+            positions.insert(id, [position.x, position.y]);
+        }
+
+        *cached = positions;
+        moved
+    }
+}
+
+impl ScopeNone<'_> {
+    /// the node under the mouse if it was double clicked with the left button in this frame
+    ///
+    /// double clicks on a pin of the node are ignored
+    pub fn node_double_clicked(&self) -> Option<NodeId> {
+        let double_clicked =
+            unsafe { imgui::sys::igIsMouseDoubleClicked(imgui::sys::ImGuiMouseButton_Left as _) };
+        if !double_clicked || self.get_hovered_pin().is_some() {
+            return None;
+        }
+        self.get_hovered_node()
+    }
+}
@@ -0,0 +1,178 @@
+/*!
+Execution pins next to data pins, like in blueprint style visual scripting.
+
+[PinKinds] remembers which pins carry the control flow instead of a value.
+Execution pins are drawn as arrows in their own color and the link events tell which kind of
+pins were involved, so e.g. a link from an execution pin to a data pin can be refused.
+
+```ignore
+let mut kinds = PinKinds::new();
+kinds.set_execution(exec_in).set_execution(exec_out);
+
+scope.add_node(node, |mut node| {
+    kinds.add_input(&mut node, exec_in, || ui.text(""));
+    kinds.add_input(&mut node, value, || ui.text("value"));
+    kinds.add_output(&mut node, exec_out, || ui.text(""));
+});
+
+if let Some(created) = kinds.links_created(&scope) {
+    if created.matches {
+        // add the link
+    }
+}
+```
+*/
+
+use crate::{
+    ColorStyle, ColorToken, InputPinId, Link, OutputPinId, PinId, PinShape, ScopeNode, ScopeNone,
+};
+use imgui::ImColor;
+use std::collections::HashSet;
+
+/// what a pin carries, see [PinKinds]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum PinKind {
+    /// a value, every pin which was not marked with [PinKinds::set_execution]
+    #[default]
+    Data,
+    /// the control flow, which node runs next
+    Execution,
+}
+
+/// link creation event together with the kinds of both pins
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct KindedLink {
+    /// the created link
+    pub link: Link,
+    /// kind of the output pin
+    pub start_kind: PinKind,
+    /// kind of the input pin
+    pub end_kind: PinKind,
+    /// both pins are of the same kind
+    pub matches: bool,
+}
+
+/// execution pins and how both kinds of pins are drawn
+#[derive(Debug, Clone)]
+pub struct PinKinds {
+    execution: HashSet<PinId>,
+    data_shape: PinShape,
+    execution_shape: PinShape,
+    execution_color: ImColor,
+}
+
+impl Default for PinKinds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PinKinds {
+    /// only data pins, drawn as filled circles, execution pins become white filled triangles
+    pub fn new() -> Self {
+        Self {
+            execution: HashSet::new(),
+            data_shape: PinShape::CircleFilled,
+            execution_shape: PinShape::TriangleFilled,
+            execution_color: ImColor::from([1.0, 1.0, 1.0]),
+        }
+    }
+
+    /// mark an input or output pin as execution pin
+    pub fn set_execution<P: Into<PinId>>(&mut self, pin: P) -> &mut Self {
+        self.execution.insert(pin.into());
+        self
+    }
+
+    /// make a pin a data pin again, e.g. after its node was removed
+    pub fn remove<P: Into<PinId>>(&mut self, pin: P) -> &mut Self {
+        self.execution.remove(&pin.into());
+        self
+    }
+
+    /// shapes of the data and the execution pins
+    pub fn set_shapes(&mut self, data: PinShape, execution: PinShape) -> &mut Self {
+        self.data_shape = data;
+        self.execution_shape = execution;
+        self
+    }
+
+    /// color of all execution pins, data pins keep [ColorStyle::Pin] of the style
+    pub fn set_execution_color<C: Into<ImColor>>(&mut self, color: C) -> &mut Self {
+        self.execution_color = color.into();
+        self
+    }
+
+    /// kind of an input or output pin
+    pub fn get<P: Into<PinId>>(&self, pin: P) -> PinKind {
+        if self.execution.contains(&pin.into()) {
+            PinKind::Execution
+        } else {
+            PinKind::Data
+        }
+    }
+
+    /// annotate a link creation event with the kinds of its pins
+    pub fn check(&self, link: Link) -> KindedLink {
+        let start_kind = self.get(link.start_pin);
+        let end_kind = self.get(link.end_pin);
+        KindedLink {
+            link,
+            start_kind,
+            end_kind,
+            matches: start_kind == end_kind,
+        }
+    }
+
+    /// [ScopeNone::links_created] annotated with the pin kinds
+    pub fn links_created(&self, scope: &ScopeNone) -> Option<KindedLink> {
+        scope.links_created().map(|link| self.check(link))
+    }
+
+    /// [ScopeNone::from_where_link_started] together with the kind of the pin
+    pub fn link_started(&self, scope: &ScopeNone) -> Option<(PinId, PinKind)> {
+        scope
+            .from_where_link_started()
+            .map(|pin| (pin, self.get(pin)))
+    }
+
+    /// [ScopeNone::from_where_link_dropped] together with the kind of the pin
+    pub fn link_dropped(
+        &self,
+        scope: &ScopeNone,
+        including_detached_links: bool,
+    ) -> Option<(PinId, PinKind)> {
+        scope
+            .from_where_link_dropped(including_detached_links)
+            .map(|pin| (pin, self.get(pin)))
+    }
+
+    /// [ScopeNode::add_input] with the shape and color of its kind
+    pub fn add_input<F: FnOnce()>(&self, scope: &mut ScopeNode<'_>, id: InputPinId, f: F) {
+        let (shape, token) = self.push(id.into());
+        scope.add_input(id, shape, f);
+        if let Some(token) = token {
+            token.pop();
+        }
+    }
+
+    /// [ScopeNode::add_output] with the shape and color of its kind
+    pub fn add_output<F: FnOnce()>(&self, scope: &mut ScopeNode<'_>, id: OutputPinId, f: F) {
+        let (shape, token) = self.push(id.into());
+        scope.add_output(id, shape, f);
+        if let Some(token) = token {
+            token.pop();
+        }
+    }
+
+    /// shape of the pin and the pushed color of execution pins
+    fn push(&self, pin: PinId) -> (PinShape, Option<ColorToken>) {
+        match self.get(pin) {
+            PinKind::Data => (self.data_shape, None),
+            PinKind::Execution => (
+                self.execution_shape,
+                Some(ColorStyle::Pin.push(self.execution_color)),
+            ),
+        }
+    }
+}
@@ -0,0 +1,1388 @@
+/*!
+Retained mode graph on top of the immediate mode scopes.
+
+[Graph] owns the nodes, pins and links together with their ids, labels and user data.
+[Graph::render] emits all the `Begin*`/ `End*` calls for one frame and applies the links
+which the user created or detached back to the graph.
+*/
+
+use crate::{
+    editor,
+    eval::{self, Cycle},
+    layout, sys,
+    typed::PinTypes,
+    ColorStyle, CoordinateSystem, DeletionRequest, EditorContext, IdentifierGenerator, InputPinId,
+    LinkId, LinkStyle, NodeId, OutputPinId, PinId, PinShape, ScopeNode, ScopeNone, StyleVar,
+};
+use imgui::{im_str, Direction, ImColor, ImString, MouseButton, StyleColor, Ui};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// input or output of a [Node]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pin<Id> {
+    /// [InputPinId] or [OutputPinId]
+    pub id: Id,
+    /// text next to the pin
+    pub label: String,
+    /// how the pin is drawn
+    pub shape: PinShape,
+    /// how many links may be connected to the pin
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub capacity: Capacity,
+}
+
+/// how many links may be connected to a pin, see [Graph::connect]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Capacity {
+    /// any number of links
+    #[default]
+    Unlimited,
+    /// at most this many links, a new link replaces the oldest one
+    Replace(usize),
+    /// at most this many links, further links are refused
+    Reject(usize),
+}
+
+/// result of [Graph::connect]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Connection {
+    /// the link was added
+    Added {
+        /// id of the new link
+        id: LinkId,
+        /// links which were removed to make room for the new one
+        displaced: Vec<Link>,
+    },
+    /// the [Capacity] of one of the pins does not allow another link,
+    /// or the pins are already linked
+    Rejected,
+}
+
+/// node with its pins and user data
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Node<N> {
+    /// id of the node in the editor
+    pub id: NodeId,
+    /// text in the title bar
+    pub title: String,
+    /// drawn top to bottom in this order
+    pub inputs: Vec<Pin<InputPinId>>,
+    /// drawn top to bottom in this order, after the inputs
+    pub outputs: Vec<Pin<OutputPinId>>,
+    /// anything the application wants to store per node
+    pub data: N,
+    /// pass-through node without title which only exists to route links,
+    /// see [Graph::insert_reroute]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reroute: bool,
+    /// only draw the title bar and all pins in one row, see [ScopeNode::add_merged_pins]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub collapsed: bool,
+}
+
+/// connection from an output pin to an input pin
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Link {
+    /// id of the link in the editor
+    pub id: LinkId,
+    /// output pin the link starts at
+    pub start: OutputPinId,
+    /// input pin the link ends at
+    pub end: InputPinId,
+}
+
+/// grid space positions of nodes and the panning of an editor,
+/// e.g. to store it next to a serialized [Graph]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EditorLayout {
+    /// [EditorContext::get_panning]
+    pub panning: [f32; 2],
+    /// grid space position of each node
+    pub positions: Vec<(NodeId, [f32; 2])>,
+}
+
+/// link which was refused by the validator of [Graph::render_validated]
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Rejected {
+    start: OutputPinId,
+    end: InputPinId,
+    /// [Ui::time] until which the pins are highlighted
+    until: f64,
+}
+
+/// nodes and links of one editor
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Graph<N> {
+    nodes: Vec<Node<N>>,
+    links: Vec<Link>,
+    id_gen: IdentifierGenerator,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    rejected: Option<Rejected>,
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_reject_flash"))]
+    reject_flash: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    displaced: Vec<Link>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    collapse_buttons: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    flow: HashMap<LinkId, f32>,
+    #[cfg_attr(feature = "serde", serde(default, with = "link_labels"))]
+    link_labels: HashMap<LinkId, String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    routing: LinkRouting,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    link_routing: HashMap<LinkId, LinkRouting>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_hovered_link: Option<LinkId>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_selected_links: Vec<LinkId>,
+    /// counts the changes of `nodes` and `links`, see [Graph::revision]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    revision: u64,
+}
+
+/// how the path of a link is drawn, see [Graph::set_routing]
+#[derive(Debug, Copy, Clone, Default)]
+pub enum LinkRouting {
+    /// the cubic bezier drawn by imnodes
+    #[default]
+    Bezier,
+    /// horizontal and vertical segments like in a circuit diagram
+    Orthogonal,
+    /// points of a polyline from the output pin to the input pin, both in screen space
+    Custom(fn([f32; 2], [f32; 2]) -> Vec<[f32; 2]>),
+}
+
+/// how far orthogonal links go straight out of a pin before turning around
+const ORTHOGONAL_MARGIN: f32 = 20.0;
+
+impl LinkRouting {
+    /// screen space polyline from `start` to `end`
+    ///
+    /// routed links are still hovered and clicked along the bezier of imnodes,
+    /// only the drawn path changes
+    pub fn path(self, start: [f32; 2], end: [f32; 2]) -> Vec<[f32; 2]> {
+        match self {
+            LinkRouting::Bezier => {
+                // same control points as imnodes
+                let length = ((end[0] - start[0]).powi(2) + (end[1] - start[1]).powi(2)).sqrt();
+                let offset = 0.25 * length;
+                let points = [
+                    start,
+                    [start[0] + offset, start[1]],
+                    [end[0] - offset, end[1]],
+                    end,
+                ];
+                (0..=BEZIER_SEGMENTS)
+                    .map(|i| {
+                        let t = i as f32 / BEZIER_SEGMENTS as f32;
+                        let u = 1.0 - t;
+                        let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+                        let mut point = [0.0, 0.0];
+                        for (weight, control) in weights.iter().zip(&points) {
+                            point[0] += weight * control[0];
+                            point[1] += weight * control[1];
+                        }
+                        point
+                    })
+                    .collect()
+            }
+            LinkRouting::Orthogonal
+                if start[0] + ORTHOGONAL_MARGIN <= end[0] - ORTHOGONAL_MARGIN =>
+            {
+                let middle = 0.5 * (start[0] + end[0]);
+                vec![start, [middle, start[1]], [middle, end[1]], end]
+            }
+            LinkRouting::Orthogonal => {
+                // the input is left of the output, so go around between the two pins
+                let middle = 0.5 * (start[1] + end[1]);
+                let right = start[0] + ORTHOGONAL_MARGIN;
+                let left = end[0] - ORTHOGONAL_MARGIN;
+                vec![
+                    start,
+                    [right, start[1]],
+                    [right, middle],
+                    [left, middle],
+                    [left, end[1]],
+                    end,
+                ]
+            }
+            LinkRouting::Custom(path) => path(start, end),
+        }
+    }
+}
+
+fn default_reject_flash() -> f64 {
+    0.5
+}
+
+/// the labels as a list of pairs, json only allows strings as keys of a map
+#[cfg(feature = "serde")]
+mod link_labels {
+    use crate::LinkId;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub(super) fn serialize<S: Serializer>(
+        labels: &HashMap<LinkId, String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut pairs: Vec<(&LinkId, &String)> = labels.iter().collect();
+        pairs.sort_by_key(|(link, _)| **link);
+        pairs.serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<LinkId, String>, D::Error> {
+        let pairs = Vec::<(LinkId, String)>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+
+/// color of the pins of a rejected link
+const REJECTED_PIN_COLOR: [f32; 3] = [0.9, 0.1, 0.1];
+
+/// padding of [Node::reroute] nodes
+const REROUTE_PADDING: f32 = 2.0;
+
+/// size of the empty content next to the pins of [Node::reroute] nodes
+const REROUTE_PIN_SIZE: [f32; 2] = [8.0, 1.0];
+
+impl<N> Default for Graph<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N> Graph<N> {
+    /// empty graph
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![],
+            links: vec![],
+            id_gen: IdentifierGenerator::new(),
+            rejected: None,
+            reject_flash: default_reject_flash(),
+            displaced: vec![],
+            collapse_buttons: false,
+            flow: HashMap::new(),
+            link_labels: HashMap::new(),
+            routing: LinkRouting::Bezier,
+            link_routing: HashMap::new(),
+            last_hovered_link: None,
+            last_selected_links: vec![],
+            revision: 0,
+        }
+    }
+
+    /// how long the pins of a link refused in [Graph::render_validated] are drawn red,
+    /// 0 turns the highlight off
+    pub fn set_reject_flash(&mut self, seconds: f64) -> &mut Self {
+        self.reject_flash = seconds;
+        self
+    }
+
+    /// draw a button in the title bar of every node which toggles [Node::collapsed]
+    pub fn set_collapse_buttons(&mut self, enabled: bool) -> &mut Self {
+        self.collapse_buttons = enabled;
+        self
+    }
+
+    /// animate dots moving along a link from its output to its input pin,
+    /// `speed` is in pixels per second, negative values move backwards and 0 turns it off
+    pub fn set_link_flow(&mut self, link: LinkId, speed: f32) -> &mut Self {
+        if speed == 0.0 {
+            self.flow.remove(&link);
+        } else {
+            self.flow.insert(link, speed);
+        }
+        self
+    }
+
+    /// text drawn in the middle of a link, e.g. a data rate or a type, `None` removes it
+    ///
+    /// see [draw_link_label], the background has the color of the nodes.
+    /// The label is removed together with the link and is saved with the `serde` feature
+    pub fn set_link_label<S: Into<String>>(&mut self, link: LinkId, label: Option<S>) -> &mut Self {
+        match label {
+            Some(label) => self.link_labels.insert(link, label.into()),
+            None => self.link_labels.remove(&link),
+        };
+        self
+    }
+
+    /// label set with [Graph::set_link_label]
+    pub fn link_label(&self, link: LinkId) -> Option<&str> {
+        self.link_labels.get(&link).map(String::as_str)
+    }
+
+    /// how all links without their own [Graph::set_link_routing] are drawn
+    pub fn set_routing(&mut self, routing: LinkRouting) -> &mut Self {
+        self.routing = routing;
+        self
+    }
+
+    /// how a single link is drawn, `None` uses the routing of the graph
+    pub fn set_link_routing(&mut self, link: LinkId, routing: Option<LinkRouting>) -> &mut Self {
+        match routing {
+            Some(routing) => self.link_routing.insert(link, routing),
+            None => self.link_routing.remove(&link),
+        };
+        self
+    }
+
+    /// speed set with [Graph::set_link_flow], 0 if the link is not animated
+    pub fn link_flow(&self, link: LinkId) -> f32 {
+        self.flow.get(&link).copied().unwrap_or(0.0)
+    }
+
+    /// changes whenever nodes or links are added, removed or borrowed with [Graph::node_mut],
+    /// so a copy of the graph only has to be refreshed if it differs
+    pub(crate) fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// all nodes in the order they are drawn
+    pub fn nodes(&self) -> &[Node<N>] {
+        &self.nodes
+    }
+
+    /// all links
+    pub fn links(&self) -> &[Link] {
+        &self.links
+    }
+
+    /// get a node by id
+    pub fn node(&self, id: NodeId) -> Option<&Node<N>> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// get a node by id
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node<N>> {
+        self.revision += 1;
+        self.nodes.iter_mut().find(|node| node.id == id)
+    }
+
+    /// get a link by id
+    pub fn link(&self, id: LinkId) -> Option<&Link> {
+        self.links.iter().find(|link| link.id == id)
+    }
+
+    /// add a node without any pins
+    pub fn add_node<S: Into<String>>(&mut self, title: S, data: N) -> NodeId {
+        let id = self.id_gen.next_node();
+        self.revision += 1;
+        self.nodes.push(Node {
+            id,
+            title: title.into(),
+            inputs: vec![],
+            outputs: vec![],
+            data,
+            reroute: false,
+            collapsed: false,
+        });
+        id
+    }
+
+    /// add an input pin to a node
+    ///
+    /// panics if the node does not exist
+    pub fn add_input<S: Into<String>>(
+        &mut self,
+        node: NodeId,
+        label: S,
+        shape: PinShape,
+    ) -> InputPinId {
+        let id = self.id_gen.next_input_pin();
+        self.node_mut(node)
+            .expect("no node with this id")
+            .inputs
+            .push(Pin {
+                id,
+                label: label.into(),
+                shape,
+                capacity: Capacity::Unlimited,
+            });
+        id
+    }
+
+    /// add an output pin to a node
+    ///
+    /// panics if the node does not exist
+    pub fn add_output<S: Into<String>>(
+        &mut self,
+        node: NodeId,
+        label: S,
+        shape: PinShape,
+    ) -> OutputPinId {
+        let id = self.id_gen.next_output_pin();
+        self.node_mut(node)
+            .expect("no node with this id")
+            .outputs
+            .push(Pin {
+                id,
+                label: label.into(),
+                shape,
+                capacity: Capacity::Unlimited,
+            });
+        id
+    }
+
+    /// add a node which already has ids, e.g. one which was removed before
+    ///
+    /// the ids must not be used by anything else in this graph
+    pub fn insert_node(&mut self, node: Node<N>) {
+        self.revision += 1;
+        self.nodes.push(node);
+    }
+
+    /// [Graph::insert_node] for a copy of a node with fresh ids for the node and its pins,
+    /// the pins keep their order
+    pub(crate) fn insert_copy(&mut self, node: &Node<N>) -> NodeId
+    where
+        N: Clone,
+    {
+        let mut copy = node.clone();
+        copy.id = self.id_gen.next_node();
+        for pin in &mut copy.inputs {
+            pin.id = self.id_gen.next_input_pin();
+        }
+        for pin in &mut copy.outputs {
+            pin.id = self.id_gen.next_output_pin();
+        }
+        let id = copy.id;
+        self.insert_node(copy);
+        id
+    }
+
+    /// [Graph::insert_node] at a position in the draw order, clamped to the number of nodes
+    pub(crate) fn insert_node_at(&mut self, index: usize, node: Node<N>) {
+        let index = index.min(self.nodes.len());
+        self.revision += 1;
+        self.nodes.insert(index, node);
+    }
+
+    /// remove a node together with all links to and from its pins
+    pub fn remove_node(&mut self, id: NodeId) -> Option<Node<N>> {
+        let index = self.nodes.iter().position(|node| node.id == id)?;
+        self.revision += 1;
+        let node = self.nodes.remove(index);
+        let attached: Vec<LinkId> = self
+            .links
+            .iter()
+            .filter(|link| {
+                node.inputs.iter().any(|pin| pin.id == link.end)
+                    || node.outputs.iter().any(|pin| pin.id == link.start)
+            })
+            .map(|link| link.id)
+            .collect();
+        for link in attached {
+            self.remove_link(link);
+        }
+        Some(node)
+    }
+
+    /// remove the nodes and links of a [DeletionRequest], ids which are not part of the graph
+    /// are skipped
+    pub fn delete(&mut self, request: &DeletionRequest) {
+        for link in &request.links {
+            self.remove_link(*link);
+        }
+        for node in &request.nodes {
+            self.remove_node(*node);
+        }
+    }
+
+    /// connect an output pin to an input pin
+    pub fn add_link(&mut self, start: OutputPinId, end: InputPinId) -> LinkId {
+        let id = self.id_gen.next_link();
+        self.revision += 1;
+        self.links.push(Link { id, start, end });
+        id
+    }
+
+    /// set how many links may end at an input pin
+    ///
+    /// panics if the pin does not exist
+    pub fn set_input_capacity(&mut self, pin: InputPinId, capacity: Capacity) -> &mut Self {
+        self.nodes
+            .iter_mut()
+            .flat_map(|node| node.inputs.iter_mut())
+            .find(|input| input.id == pin)
+            .expect("no input pin with this id")
+            .capacity = capacity;
+        self
+    }
+
+    /// set how many links may start at an output pin
+    ///
+    /// panics if the pin does not exist
+    pub fn set_output_capacity(&mut self, pin: OutputPinId, capacity: Capacity) -> &mut Self {
+        self.nodes
+            .iter_mut()
+            .flat_map(|node| node.outputs.iter_mut())
+            .find(|output| output.id == pin)
+            .expect("no output pin with this id")
+            .capacity = capacity;
+        self
+    }
+
+    /// like [Graph::add_link] but respects the [Capacity] of both pins,
+    /// pins which are not part of the graph have no limit
+    pub fn connect(&mut self, start: OutputPinId, end: InputPinId) -> Connection {
+        if self
+            .links
+            .iter()
+            .any(|link| link.start == start && link.end == end)
+        {
+            return Connection::Rejected;
+        }
+        let start_capacity = self
+            .nodes
+            .iter()
+            .flat_map(|node| node.outputs.iter())
+            .find(|output| output.id == start)
+            .map_or(Capacity::Unlimited, |output| output.capacity);
+        let end_capacity = self
+            .nodes
+            .iter()
+            .flat_map(|node| node.inputs.iter())
+            .find(|input| input.id == end)
+            .map_or(Capacity::Unlimited, |input| input.capacity);
+
+        // number of links which have to go, checked for both pins before anything is removed
+        let excess = |capacity: Capacity, connected: usize| match capacity {
+            Capacity::Unlimited => Some(0),
+            Capacity::Replace(max) if max > 0 => Some((connected + 1).saturating_sub(max)),
+            Capacity::Replace(_) => None,
+            Capacity::Reject(max) if connected < max => Some(0),
+            Capacity::Reject(_) => None,
+        };
+        let start_links: Vec<LinkId> = self
+            .links
+            .iter()
+            .filter(|link| link.start == start)
+            .map(|link| link.id)
+            .collect();
+        let end_links: Vec<LinkId> = self
+            .links
+            .iter()
+            .filter(|link| link.end == end)
+            .map(|link| link.id)
+            .collect();
+        let (start_excess, end_excess) = match (
+            excess(start_capacity, start_links.len()),
+            excess(end_capacity, end_links.len()),
+        ) {
+            (Some(start_excess), Some(end_excess)) => (start_excess, end_excess),
+            _ => return Connection::Rejected,
+        };
+
+        // links are kept in creation order, so the first ones are the oldest
+        let mut displaced = vec![];
+        for id in start_links[..start_excess]
+            .iter()
+            .chain(&end_links[..end_excess])
+        {
+            if let Some(link) = self.remove_link(*id) {
+                displaced.push(link);
+            }
+        }
+        let id = self.add_link(start, end);
+        Connection::Added { id, displaced }
+    }
+
+    /// links which [Graph::render] removed since the last call because a new link took
+    /// their place, see [Capacity::Replace]
+    pub fn take_displaced_links(&mut self) -> Vec<Link> {
+        std::mem::take(&mut self.displaced)
+    }
+
+    /// split a link into two by putting a [Node::reroute] node in between
+    ///
+    /// returns `None` if there is no such link
+    pub fn insert_reroute(&mut self, link: LinkId, data: N) -> Option<NodeId> {
+        let link = self.remove_link(link)?;
+        let node = self.add_node("", data);
+        let input = self.add_input(node, "", PinShape::CircleFilled);
+        let output = self.add_output(node, "", PinShape::CircleFilled);
+        self.set_input_capacity(input, Capacity::Replace(1));
+        self.node_mut(node).expect("node was just added").reroute = true;
+        self.add_link(link.start, input);
+        self.add_link(output, link.end);
+        Some(node)
+    }
+
+    /// [Graph::insert_reroute] at the mouse position if a link was double clicked this frame,
+    /// `data` creates the user data of the new node
+    pub fn reroute_on_double_click<D: FnOnce() -> N>(
+        &mut self,
+        ui: &Ui,
+        scope: &ScopeNone,
+        data: D,
+    ) -> Option<NodeId> {
+        if !ui.is_mouse_double_clicked(MouseButton::Left) {
+            return None;
+        }
+        let link = scope.get_hovered_link()?;
+        let node = self.insert_reroute(link, data())?;
+        let [x, y] = scope.guard.context.screen_to_grid(ui.io().mouse_pos);
+        node.set_position(x, y, CoordinateSystem::GridSpace);
+        Some(node)
+    }
+
+    /// add a link which already has an id, e.g. one which was removed before
+    ///
+    /// the id must not be used by another link in this graph
+    pub fn insert_link(&mut self, link: Link) {
+        self.revision += 1;
+        self.links.push(link);
+    }
+
+    /// let the generator continue after `highest`, before nodes, pins or links with ids up to
+    /// it are inserted
+    #[cfg(feature = "graphml")]
+    pub(crate) fn reserve_ids(&mut self, highest: i32) {
+        self.id_gen = IdentifierGenerator::starting_at(highest + 1);
+    }
+
+    /// let the generator continue after the highest id in the graph,
+    /// after nodes and links with ids from somewhere else were inserted
+    #[cfg(feature = "petgraph")]
+    pub(crate) fn skip_used_ids(&mut self) {
+        let nodes = self.nodes.iter().map(|node| node.id.id);
+        let pins = self.nodes.iter().flat_map(|node| {
+            let inputs = node.inputs.iter().map(|pin| pin.id.id);
+            inputs.chain(node.outputs.iter().map(|pin| pin.id.id))
+        });
+        let links = self.links.iter().map(|link| link.id.id);
+        let highest = nodes.chain(pins).chain(links).max().unwrap_or(-1);
+        self.id_gen = IdentifierGenerator::starting_at(highest + 1);
+    }
+
+    /// remove a link
+    pub fn remove_link(&mut self, id: LinkId) -> Option<Link> {
+        let index = self.links.iter().position(|link| link.id == id)?;
+        self.flow.remove(&id);
+        self.link_labels.remove(&id);
+        self.link_routing.remove(&id);
+        self.revision += 1;
+        Some(self.links.remove(index))
+    }
+
+    /// node which owns the input pin
+    pub fn input_owner(&self, pin: InputPinId) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .find(|node| node.inputs.iter().any(|input| input.id == pin))
+            .map(|node| node.id)
+    }
+
+    /// node which owns the output pin
+    pub fn output_owner(&self, pin: OutputPinId) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .find(|node| node.outputs.iter().any(|output| output.id == pin))
+            .map(|node| node.id)
+    }
+
+    /// the cycle a link from `start` to `end` would close, `None` if the graph stays acyclic
+    ///
+    /// meant for [Graph::render_validated] in editors which only allow DAGs
+    pub fn would_create_cycle(&self, start: OutputPinId, end: InputPinId) -> Option<Cycle> {
+        let from = self.output_owner(start)?;
+        let to = self.input_owner(end)?;
+        eval::would_create_cycle(&layout::graph_edges(self), from, to)
+    }
+
+    /// current panning and positions of all nodes of this graph
+    pub fn capture_layout(&self, context: &EditorContext) -> EditorLayout {
+        let ids: Vec<NodeId> = self.nodes.iter().map(|node| node.id).collect();
+        let positions = context.capture_positions(&ids);
+        let _previous = context.make_current();
+        let panning = context.get_panning();
+        EditorLayout {
+            panning: [panning.x, panning.y],
+            positions: ids
+                .into_iter()
+                .zip(positions)
+                .map(|(id, position)| (id, [position.x, position.y]))
+                .collect(),
+        }
+    }
+
+    /// [EditorContext::frame_nodes] with all nodes of this graph
+    pub fn frame_all(&self, context: &EditorContext) {
+        let ids: Vec<NodeId> = self.nodes.iter().map(|node| node.id).collect();
+        context.frame_nodes(&ids);
+    }
+
+    /// move the nodes and the panning to a layout created with [Graph::capture_layout],
+    /// nodes which are not part of this graph are skipped
+    pub fn apply_layout(&self, context: &EditorContext, layout: &EditorLayout) {
+        let _previous = context.make_current();
+        let [x, y] = layout.panning;
+        context.reset_panning(crate::ImVec2 { x, y });
+        for (id, [x, y]) in &layout.positions {
+            if self.node(*id).is_some() {
+                id.set_position(*x, *y, CoordinateSystem::GridSpace);
+            }
+        }
+    }
+
+    /// draw the whole graph and apply the links which were created or detached by the user
+    ///
+    /// new links are added with [Graph::connect], so the [Capacity] of the pins is respected
+    ///
+    /// `body` is called for every node after its inputs and before its outputs,
+    /// e.g. to add static attributes with widgets for the node data
+    pub fn render<'a, F>(
+        &mut self,
+        ui: &Ui,
+        context: &'a mut EditorContext,
+        body: F,
+    ) -> ScopeNone<'a>
+    where
+        F: FnMut(&mut ScopeNode<'_>, &mut Node<N>),
+    {
+        self.render_validated(ui, context, |_, _| true, body)
+    }
+
+    /// like [Graph::render] but a link created by the user is only added if `validate` returns
+    /// true for it, e.g. to check that the pin types match
+    ///
+    /// the pins of a refused link are drawn red for a moment, see [Graph::set_reject_flash]
+    pub fn render_validated<'a, F, V>(
+        &mut self,
+        ui: &Ui,
+        context: &'a mut EditorContext,
+        validate: V,
+        body: F,
+    ) -> ScopeNone<'a>
+    where
+        F: FnMut(&mut ScopeNode<'_>, &mut Node<N>),
+        V: FnMut(&Graph<N>, &crate::Link) -> bool,
+    {
+        self.render_with(
+            ui,
+            context,
+            validate,
+            None::<&PinTypes<()>>,
+            &HashSet::new(),
+            body,
+        )
+    }
+
+    /// like [Graph::render] but nodes outside of [EditorContext::visible_rect] are skipped,
+    /// which keeps graphs with thousands of nodes fast
+    ///
+    /// a node is still drawn if a link to it might cross the view (judged by the bounding box
+    /// of both nodes) and links are only drawn if both of their nodes are.
+    /// Skipped nodes keep their position but can not be hovered or selected.
+    pub fn render_culled<'a, F>(
+        &mut self,
+        ui: &Ui,
+        context: &'a mut EditorContext,
+        body: F,
+    ) -> ScopeNone<'a>
+    where
+        F: FnMut(&mut ScopeNode<'_>, &mut Node<N>),
+    {
+        let culled = self.culled_nodes(context);
+        self.render_with(
+            ui,
+            context,
+            |_, _| true,
+            None::<&PinTypes<()>>,
+            &culled,
+            body,
+        )
+    }
+
+    /// like [Graph::render] but only links between pins of matching types are added
+    /// and every pin is drawn in the color and shape of its type,
+    /// links are colored as chosen with [PinTypes::set_link_coloring]
+    pub fn render_typed<'a, T, F>(
+        &mut self,
+        ui: &Ui,
+        context: &'a mut EditorContext,
+        types: &PinTypes<T>,
+        body: F,
+    ) -> ScopeNone<'a>
+    where
+        T: Copy + Eq + Hash,
+        F: FnMut(&mut ScopeNode<'_>, &mut Node<N>),
+    {
+        self.render_with(
+            ui,
+            context,
+            |_, link| types.accepts(link),
+            Some(types),
+            &HashSet::new(),
+            body,
+        )
+    }
+
+    fn render_with<'a, F, V, T>(
+        &mut self,
+        ui: &Ui,
+        context: &'a mut EditorContext,
+        mut validate: V,
+        types: Option<&PinTypes<T>>,
+        culled: &HashSet<NodeId>,
+        mut body: F,
+    ) -> ScopeNone<'a>
+    where
+        F: FnMut(&mut ScopeNode<'_>, &mut Node<N>),
+        V: FnMut(&Graph<N>, &crate::Link) -> bool,
+        T: Copy + Eq + Hash,
+    {
+        let rejected = self.rejected.filter(|rejected| ui.time() < rejected.until);
+        // pushed color and shape of a pin, `shape` is used if the style has none
+        let look = |pin: PinId, shape: PinShape| {
+            let color = types.and_then(|types| types.color_of(pin));
+            let styled_shape = types.and_then(|types| types.shape_of(pin));
+            let is_rejected = rejected
+                .is_some_and(|rejected| pin == rejected.start.into() || pin == rejected.end.into());
+            let color = if is_rejected {
+                Some(ImColor::from(REJECTED_PIN_COLOR))
+            } else {
+                color
+            };
+            (
+                color.map(|color| ColorStyle::Pin.push(color)),
+                styled_shape.unwrap_or(shape),
+            )
+        };
+        let link_colors =
+            |link: &Link| types.and_then(|types| types.link_colors(link.start, link.end));
+        let collapse_buttons = self.collapse_buttons;
+        let Self {
+            nodes,
+            links,
+            flow,
+            link_labels,
+            routing,
+            link_routing,
+            last_hovered_link,
+            last_selected_links,
+            ..
+        } = self;
+
+        let (scope, ()) = editor(ui, context, |mut editor| {
+            // screen space position of every pin in this frame, for the flow animation
+            let mut pin_positions: HashMap<PinId, [f32; 2]> = HashMap::new();
+            let style = unsafe { &*sys::imnodes_GetStyle() };
+
+            for node in nodes.iter_mut().filter(|node| !culled.contains(&node.id)) {
+                // vertical center of the attribute of each pin, the side is decided by `is_input`
+                let mut rows: Vec<(PinId, bool, f32)> = vec![];
+                let mut padding = style.node_padding_horizontal;
+
+                if node.reroute {
+                    // the padding is read in BeginNode, so it has to be pushed around add_node
+                    let horizontal = StyleVar::NodePaddingHorizontal.push(REROUTE_PADDING);
+                    let vertical = StyleVar::NodePaddingVertical.push(REROUTE_PADDING);
+                    padding = REROUTE_PADDING;
+                    editor.add_node(node.id, |mut scope| {
+                        for pin in &node.inputs {
+                            let (token, shape) = look(pin.id.into(), pin.shape);
+                            scope.add_input(pin.id, shape, || ui.dummy(REROUTE_PIN_SIZE));
+                            rows.push((pin.id.into(), true, item_center_y()));
+                            if let Some(token) = token {
+                                token.pop();
+                            }
+                        }
+                        for pin in &node.outputs {
+                            let (token, shape) = look(pin.id.into(), pin.shape);
+                            scope.add_output(pin.id, shape, || ui.dummy(REROUTE_PIN_SIZE));
+                            rows.push((pin.id.into(), false, item_center_y()));
+                            if let Some(token) = token {
+                                token.pop();
+                            }
+                        }
+                    });
+                    vertical.pop();
+                    horizontal.pop();
+                } else {
+                    editor.add_node(node.id, |mut scope| {
+                        scope.add_titlebar(|| {
+                            if collapse_buttons {
+                                // BeginNode pushes the node id, so the label is unique per node
+                                let direction = if node.collapsed {
+                                    Direction::Right
+                                } else {
+                                    Direction::Down
+                                };
+                                if ui.arrow_button(im_str!("collapse"), direction) {
+                                    node.collapsed = !node.collapsed;
+                                }
+                                ui.same_line(0.0);
+                            }
+                            ui.text(&node.title)
+                        });
+
+                        if node.collapsed {
+                            // the pin colors are left out, all pins of a side share one spot anyway
+                            let inputs: Vec<_> =
+                                node.inputs.iter().map(|pin| (pin.id, pin.shape)).collect();
+                            let outputs: Vec<_> =
+                                node.outputs.iter().map(|pin| (pin.id, pin.shape)).collect();
+                            scope.add_merged_pins(&inputs, &outputs);
+                            let y = item_center_y();
+                            rows.extend(node.inputs.iter().map(|pin| (pin.id.into(), true, y)));
+                            rows.extend(node.outputs.iter().map(|pin| (pin.id.into(), false, y)));
+                            return;
+                        }
+
+                        for pin in &node.inputs {
+                            let (token, shape) = look(pin.id.into(), pin.shape);
+                            scope.add_input(pin.id, shape, || ui.text(&pin.label));
+                            rows.push((pin.id.into(), true, item_center_y()));
+                            if let Some(token) = token {
+                                token.pop();
+                            }
+                        }
+                        body(&mut scope, node);
+                        for pin in &node.outputs {
+                            let (token, shape) = look(pin.id.into(), pin.shape);
+                            scope.add_output(pin.id, shape, || ui.text(&pin.label));
+                            rows.push((pin.id.into(), false, item_center_y()));
+                            if let Some(token) = token {
+                                token.pop();
+                            }
+                        }
+                    });
+                }
+
+                // EndNode leaves the content of the node as the last item,
+                // the pins sit outside of it by the padding and the pin offset
+                let (min, max) = item_rect();
+                for (pin, is_input, y) in rows {
+                    let x = if is_input {
+                        min[0] - padding - style.pin_offset
+                    } else {
+                        max[0] + padding + style.pin_offset
+                    };
+                    pin_positions.insert(pin, [x, y]);
+                }
+            }
+
+            // imnodes always draws a bezier in one color, so routed and gradient links are drawn
+            // invisible by imnodes (which keeps them clickable) and the path is drawn on top
+            let hidden = LinkStyle {
+                color: Some(ImColor::from([0.0, 0.0, 0.0, 0.0])),
+                hovered: Some(ImColor::from([0.0, 0.0, 0.0, 0.0])),
+                selected: Some(ImColor::from([0.0, 0.0, 0.0, 0.0])),
+            };
+            let routing_of = |id: &LinkId| *link_routing.get(id).unwrap_or(routing);
+            // a link to a pin which was not submitted would make imnodes create an empty pin
+            let links: Vec<&Link> = links
+                .iter()
+                .filter(|link| {
+                    pin_positions.contains_key(&link.start.into())
+                        && pin_positions.contains_key(&link.end.into())
+                })
+                .collect();
+            // the links which are drawn here instead of by imnodes
+            let drawn = |link: &Link| {
+                !matches!(routing_of(&link.id), LinkRouting::Bezier)
+                    || matches!(link_colors(link), Some((start, end)) if start != end)
+            };
+            for link in &links {
+                match link_colors(link) {
+                    _ if drawn(link) => {
+                        editor.add_link_styled(link.id, link.end, link.start, hidden)
+                    }
+                    Some((color, _)) => {
+                        let solid = LinkStyle {
+                            color: Some(color),
+                            ..LinkStyle::default()
+                        };
+                        editor.add_link_styled(link.id, link.end, link.start, solid)
+                    }
+                    None => editor.add_link(link.id, link.end, link.start),
+                }
+            }
+
+            let flow_color = style.colors[ColorStyle::LinkSelected as usize];
+            for link in &links {
+                let routing = routing_of(&link.id);
+                let speed = flow.get(&link.id).copied();
+                let label = link_labels.get(&link.id);
+                let drawn = drawn(link);
+                if !drawn && speed.is_none() && label.is_none() {
+                    continue;
+                }
+                let start = pin_positions.get(&link.start.into());
+                let end = pin_positions.get(&link.end.into());
+                let path = match (start, end) {
+                    (Some(start), Some(end)) => routing.path(*start, *end),
+                    _ => continue,
+                };
+
+                if drawn {
+                    // hover and selection are only known after EndNodeEditor, so use the last frame
+                    let state = if last_hovered_link == &Some(link.id) {
+                        Some(ColorStyle::LinkHovered)
+                    } else if last_selected_links.contains(&link.id) {
+                        Some(ColorStyle::LinkSelected)
+                    } else {
+                        None
+                    };
+                    let (start, end) = match (state, link_colors(link)) {
+                        (None, Some(colors)) => colors,
+                        (state, _) => {
+                            let color = state.unwrap_or(ColorStyle::Link);
+                            let color = ImColor::from(style.colors[color as usize]);
+                            (color, color)
+                        }
+                    };
+                    let draw_list = ui.get_window_draw_list();
+                    let segments = path.len().saturating_sub(1).max(1) as f32;
+                    for (i, pair) in path.windows(2).enumerate() {
+                        let color = lerp_color(start, end, (i as f32 + 0.5) / segments);
+                        draw_list
+                            .add_line(pair[0], pair[1], color)
+                            .thickness(style.link_thickness)
+                            .build();
+                    }
+                }
+                if let Some(speed) = speed {
+                    draw_flow(ui, &path, speed, flow_color);
+                }
+                if let Some(label) = label {
+                    let background = style.colors[ColorStyle::NodeBackground as usize];
+                    draw_link_label(ui, &path, label, Some(ImColor::from(background)));
+                }
+            }
+        });
+
+        self.last_hovered_link = scope.get_hovered_link();
+        self.last_selected_links = scope.selected_links();
+
+        if let Some(created) = scope.links_created() {
+            let connection = if validate(self, &created) {
+                self.connect(created.start_pin, created.end_pin)
+            } else {
+                Connection::Rejected
+            };
+            if let Connection::Added { displaced, .. } = connection {
+                self.displaced.extend(displaced);
+            } else {
+                self.rejected = Some(Rejected {
+                    start: created.start_pin,
+                    end: created.end_pin,
+                    until: ui.time() + self.reject_flash,
+                });
+            }
+        }
+        if let Some(destroyed) = scope.link_destroyed() {
+            self.remove_link(destroyed);
+        }
+
+        scope
+    }
+
+    /// nodes which are neither in the view nor linked across it
+    fn culled_nodes(&self, context: &EditorContext) -> HashSet<NodeId> {
+        // positions, sizes and the panning are stored per editor
+        let _current = context.make_current();
+        let (view_min, view_max) = context.visible_rect();
+        let intersects = |(min, max): ([f32; 2], [f32; 2])| {
+            min[0] <= view_max[0]
+                && max[0] >= view_min[0]
+                && min[1] <= view_max[1]
+                && max[1] >= view_min[1]
+        };
+
+        let rects: HashMap<NodeId, ([f32; 2], [f32; 2])> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let position = node.id.get_position(CoordinateSystem::GridSpace);
+                let size = node.id.get_dimensions();
+                let min = [position.x, position.y];
+                (node.id, (min, [min[0] + size.x, min[1] + size.y]))
+            })
+            .collect();
+        let owners: HashMap<PinId, NodeId> = self
+            .nodes
+            .iter()
+            .flat_map(|node| {
+                let inputs = node.inputs.iter().map(|pin| pin.id.into());
+                let outputs = node.outputs.iter().map(|pin| pin.id.into());
+                inputs.chain(outputs).map(move |pin: PinId| (pin, node.id))
+            })
+            .collect();
+
+        // nodes which were never drawn have no size yet, so they are drawn once to get one
+        let mut shown: HashSet<NodeId> = rects
+            .iter()
+            .filter(|(_, (min, max))| min == max || intersects((*min, *max)))
+            .map(|(id, _)| *id)
+            .collect();
+        for link in &self.links {
+            let start = owners.get(&link.start.into());
+            let end = owners.get(&link.end.into());
+            if let (Some(start), Some(end)) = (start, end) {
+                let (start_min, start_max) = rects[start];
+                let (end_min, end_max) = rects[end];
+                let bounds = (
+                    [start_min[0].min(end_min[0]), start_min[1].min(end_min[1])],
+                    [start_max[0].max(end_max[0]), start_max[1].max(end_max[1])],
+                );
+                if intersects(bounds) {
+                    shown.insert(*start);
+                    shown.insert(*end);
+                }
+            }
+        }
+
+        rects.into_keys().filter(|id| !shown.contains(id)).collect()
+    }
+}
+
+/// GetItemRectMin, GetItemRectMax
+fn item_rect() -> ([f32; 2], [f32; 2]) {
+    let mut min = imgui::sys::ImVec2::zero();
+    let mut max = imgui::sys::ImVec2::zero();
+    unsafe {
+        imgui::sys::igGetItemRectMin(&mut min);
+        imgui::sys::igGetItemRectMax(&mut max);
+    }
+    ([min.x, min.y], [max.x, max.y])
+}
+
+fn item_center_y() -> f32 {
+    let (min, max) = item_rect();
+    0.5 * (min[1] + max[1])
+}
+
+/// distance between the dots of [Graph::set_link_flow]
+const FLOW_SPACING: f32 = 30.0;
+
+/// radius of the dots of [Graph::set_link_flow]
+const FLOW_RADIUS: f32 = 3.0;
+
+/// number of straight pieces a bezier link is split into
+const BEZIER_SEGMENTS: usize = 32;
+
+/// color `t` of the way from `a` to `b`
+fn lerp_color(a: ImColor, b: ImColor, t: f32) -> ImColor {
+    let (a, b): (u32, u32) = (a.into(), b.into());
+    let channel = |shift: u32| {
+        let a = ((a >> shift) & 0xff) as f32;
+        let b = ((b >> shift) & 0xff) as f32;
+        ((a + t * (b - a)).round() as u32) << shift
+    };
+    ImColor::from(channel(0) | channel(8) | channel(16) | channel(24))
+}
+
+/// space between a link label and the border of its background
+const LABEL_PADDING: f32 = 3.0;
+
+/// point halfway along a path by arc length, `None` for an empty path
+fn path_midpoint(path: &[[f32; 2]]) -> Option<[f32; 2]> {
+    let lengths: Vec<f32> = path
+        .windows(2)
+        .map(|pair| ((pair[1][0] - pair[0][0]).powi(2) + (pair[1][1] - pair[0][1]).powi(2)).sqrt())
+        .collect();
+    let mut remaining = 0.5 * lengths.iter().sum::<f32>();
+    for (pair, length) in path.windows(2).zip(lengths) {
+        if remaining <= length {
+            let t = remaining / length.max(f32::EPSILON);
+            return Some([
+                pair[0][0] + t * (pair[1][0] - pair[0][0]),
+                pair[0][1] + t * (pair[1][1] - pair[0][1]),
+            ]);
+        }
+        remaining -= length;
+    }
+    path.last().copied()
+}
+
+/// text centered on the middle of a screen space path, optionally on a rounded background
+///
+/// call it inside of the editor after the pins were submitted, e.g. with the path of
+/// [LinkRouting::path] between the pin positions of this frame, so the label follows panning
+pub fn draw_link_label(ui: &Ui, path: &[[f32; 2]], text: &str, background: Option<ImColor>) {
+    let center = match path_midpoint(path) {
+        Some(center) => center,
+        None => return,
+    };
+    let size = ui.calc_text_size(&ImString::new(text), false, -1.0);
+    let min = [center[0] - 0.5 * size[0], center[1] - 0.5 * size[1]];
+
+    let draw_list = ui.get_window_draw_list();
+    if let Some(background) = background {
+        draw_list
+            .add_rect(
+                [min[0] - LABEL_PADDING, min[1] - LABEL_PADDING],
+                [
+                    min[0] + size[0] + LABEL_PADDING,
+                    min[1] + size[1] + LABEL_PADDING,
+                ],
+                background,
+            )
+            .rounding(LABEL_PADDING)
+            .filled(true)
+            .build();
+    }
+    draw_list.add_text(min, ui.style_color(StyleColor::Text), text);
+}
+
+/// dots moving along a path, evenly spaced by arc length
+fn draw_flow(ui: &Ui, path: &[[f32; 2]], speed: f32, color: u32) {
+    // arc length at each point of the path
+    let mut lengths = vec![0.0];
+    for pair in path.windows(2) {
+        let step = ((pair[1][0] - pair[0][0]).powi(2) + (pair[1][1] - pair[0][1]).powi(2)).sqrt();
+        lengths.push(lengths[lengths.len() - 1] + step);
+    }
+    let total = lengths[lengths.len() - 1];
+    if total <= 0.0 {
+        return;
+    }
+
+    let draw_list = ui.get_window_draw_list();
+    let color = ImColor::from(color);
+    let mut distance = (ui.time() as f32 * speed).rem_euclid(FLOW_SPACING);
+    while distance < total {
+        let segment = lengths.partition_point(|length| *length < distance).max(1);
+        let (a, b) = (path[segment - 1], path[segment]);
+        let before = lengths[segment - 1];
+        let t = (distance - before) / (lengths[segment] - before).max(f32::EPSILON);
+        let point = [a[0] + t * (b[0] - a[0]), a[1] + t * (b[1] - a[1])];
+        draw_list
+            .add_circle(point, FLOW_RADIUS, color)
+            .filled(true)
+            .build();
+        distance += FLOW_SPACING;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// graph with an output pin on one node and an input pin on another
+    fn pins() -> (Graph<()>, OutputPinId, InputPinId) {
+        let mut graph = Graph::new();
+        let source = graph.add_node("source", ());
+        let sink = graph.add_node("sink", ());
+        let output = graph.add_output(source, "out", PinShape::CircleFilled);
+        let input = graph.add_input(sink, "in", PinShape::CircleFilled);
+        (graph, output, input)
+    }
+
+    #[test]
+    fn single_input_replaces_its_link() {
+        let (mut graph, output, input) = pins();
+        let node = graph.nodes()[0].id;
+        let other = graph.add_output(node, "other", PinShape::CircleFilled);
+        graph.set_input_capacity(input, Capacity::Replace(1));
+
+        let first = match graph.connect(output, input) {
+            Connection::Added { id, displaced } => {
+                assert!(displaced.is_empty());
+                id
+            }
+            Connection::Rejected => panic!("first link was rejected"),
+        };
+        match graph.connect(other, input) {
+            Connection::Added { id, displaced } => {
+                assert_eq!(displaced.len(), 1);
+                assert_eq!(displaced[0].id, first);
+                assert_eq!(graph.links().len(), 1);
+                assert_eq!(graph.links()[0].id, id);
+                assert_eq!(graph.links()[0].start, other);
+            }
+            Connection::Rejected => panic!("replacing link was rejected"),
+        }
+    }
+
+    #[test]
+    fn full_reject_pin_refuses_links() {
+        let (mut graph, output, input) = pins();
+        let node = graph.nodes()[0].id;
+        let other = graph.add_output(node, "other", PinShape::CircleFilled);
+        graph.set_input_capacity(input, Capacity::Reject(1));
+
+        assert!(matches!(
+            graph.connect(output, input),
+            Connection::Added { .. }
+        ));
+        assert_eq!(graph.connect(other, input), Connection::Rejected);
+        assert_eq!(graph.links().len(), 1);
+    }
+
+    #[test]
+    fn duplicate_link_is_rejected() {
+        let (mut graph, output, input) = pins();
+
+        assert!(matches!(
+            graph.connect(output, input),
+            Connection::Added { .. }
+        ));
+        assert_eq!(graph.connect(output, input), Connection::Rejected);
+        assert_eq!(graph.links().len(), 1);
+    }
+
+    #[test]
+    fn unknown_pins_have_no_limit() {
+        let (mut graph, output, input) = pins();
+        graph.set_output_capacity(output, Capacity::Reject(1));
+        let unknown_input = InputPinId { id: 1000 };
+        let unknown_output = OutputPinId { id: 1001 };
+
+        assert!(matches!(
+            graph.connect(output, input),
+            Connection::Added { .. }
+        ));
+        // the capacity of the known end still applies
+        assert_eq!(graph.connect(output, unknown_input), Connection::Rejected);
+        for start in [unknown_output, OutputPinId { id: 1002 }] {
+            let connection = graph.connect(start, unknown_input);
+            assert!(matches!(connection, Connection::Added { .. }));
+        }
+        assert_eq!(graph.links().len(), 3);
+    }
+
+    #[test]
+    fn removed_links_lose_their_label() {
+        let (mut graph, output, input) = pins();
+        let link = graph.add_link(output, input);
+        graph.set_link_label(link, Some("label"));
+        assert_eq!(graph.link_label(link), Some("label"));
+
+        let node = graph.input_owner(input).unwrap();
+        graph.remove_node(node);
+        assert_eq!(graph.link_label(link), None);
+        assert!(graph.link_labels.is_empty());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn link_labels_are_serialized() {
+        let (mut graph, output, input) = pins();
+        let link = graph.add_link(output, input);
+        graph.set_link_label(link, Some("8 kb/s"));
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let loaded: Graph<()> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.link_label(link), Some("8 kb/s"));
+    }
+}
@@ -0,0 +1,604 @@
+/*!
+Import and export of a [Graph] as [GraphML](http://graphml.graphdrawing.org), e.g. to exchange
+graphs with yEd or Gephi.
+
+Nodes have a `label` (the title) and, if a layout is given, `x` and `y` in grid space.
+Every pin is a port with a `label`, a `direction` (`input` or `output`) and a `shape`,
+links are edges between ports.
+
+Files from other tools usually have neither ports nor directions. Edges without ports get one
+shared output and input pin per node, undirected ports become an output when they are used as
+the source of an edge and an input when they are used as the target.
+The node data is not stored, imported nodes get `N::default()`.
+The ids written by [Graph::to_graphml] (`n3`, `p7`, `e2`) are kept on import, so a graph
+refers to the same nodes, pins and links after it was saved and loaded again.
+Elements with other ids get fresh ones.
+
+```ignore
+std::fs::write("graph.graphml", graph.to_graphml_with_layout(&graph.capture_layout(&context)))?;
+let (graph, layout) = Graph::<MyData>::from_graphml(&std::fs::read_to_string("graph.graphml")?)?;
+graph.apply_layout(&context, &layout);
+```
+*/
+
+use crate::{
+    graph::{Capacity, EditorLayout, Graph, Link, Node, Pin},
+    InputPinId, LinkId, NodeId, OutputPinId, PinShape,
+};
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fmt::Write,
+};
+
+/// GraphML could not be imported
+#[derive(Debug)]
+pub enum GraphMlError {
+    /// the file is no well formed xml
+    Xml(quick_xml::Error),
+    /// an element misses an attribute which is required by GraphML
+    MissingAttribute(&'static str),
+    /// an edge refers to a node which does not exist
+    UnknownNode(String),
+}
+
+impl fmt::Display for GraphMlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphMlError::Xml(error) => write!(f, "invalid xml: {}", error),
+            GraphMlError::MissingAttribute(name) => write!(f, "missing attribute {}", name),
+            GraphMlError::UnknownNode(id) => write!(f, "edge to unknown node {}", id),
+        }
+    }
+}
+
+impl std::error::Error for GraphMlError {}
+
+impl From<quick_xml::Error> for GraphMlError {
+    fn from(error: quick_xml::Error) -> Self {
+        GraphMlError::Xml(error)
+    }
+}
+
+impl<N> Graph<N> {
+    /// GraphML with the titles, pins and links
+    pub fn to_graphml(&self) -> String {
+        self.write_graphml(&HashMap::new())
+    }
+
+    /// like [Graph::to_graphml] with the grid space positions of the layout as `x` and `y`
+    pub fn to_graphml_with_layout(&self, layout: &EditorLayout) -> String {
+        let positions = layout.positions.iter().copied().collect();
+        self.write_graphml(&positions)
+    }
+
+    /// graph from GraphML and the positions of the nodes which have `x` and `y`
+    pub fn from_graphml(xml: &str) -> Result<(Self, EditorLayout), GraphMlError>
+    where
+        N: Default,
+    {
+        let parsed = parse(xml)?;
+        let mut builder = Builder {
+            graph: Graph::new(),
+            nodes: HashMap::new(),
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            undirected: HashMap::new(),
+        };
+        let mut layout = EditorLayout::default();
+        let mut kept = KeptIds::of(&parsed);
+        builder.graph.reserve_ids(kept.highest);
+
+        for node in parsed.nodes {
+            let id = match kept.nodes.remove(&node.id) {
+                Some(id) => {
+                    builder.graph.insert_node(Node {
+                        id,
+                        title: node.title,
+                        inputs: vec![],
+                        outputs: vec![],
+                        data: N::default(),
+                        reroute: false,
+                        collapsed: false,
+                    });
+                    id
+                }
+                None => builder.graph.add_node(node.title, N::default()),
+            };
+            if let (Some(x), Some(y)) = (node.x, node.y) {
+                layout.positions.push((id, [x, y]));
+            }
+            for port in node.ports {
+                let key = (node.id.clone(), port.name.clone());
+                let kept_pin = kept.pins.remove(&key);
+                match port.direction {
+                    Some(Direction::Input) => {
+                        let pin = match kept_pin {
+                            Some(pin) => {
+                                let pin = InputPinId { id: pin };
+                                builder.insert_input(id, pin, port.label, port.shape);
+                                pin
+                            }
+                            None => builder.graph.add_input(id, port.label, port.shape),
+                        };
+                        builder.inputs.insert(key, pin);
+                    }
+                    Some(Direction::Output) => {
+                        let pin = match kept_pin {
+                            Some(pin) => {
+                                let pin = OutputPinId { id: pin };
+                                builder.insert_output(id, pin, port.label, port.shape);
+                                pin
+                            }
+                            None => builder.graph.add_output(id, port.label, port.shape),
+                        };
+                        builder.outputs.insert(key, pin);
+                    }
+                    None => {
+                        builder.undirected.insert(key, port);
+                    }
+                }
+            }
+            builder.nodes.insert(node.id, id);
+        }
+
+        for edge in parsed.edges {
+            let id = edge.id.as_ref().and_then(|id| kept.links.remove(id));
+            let start = builder.output(edge.source, edge.source_port)?;
+            let end = builder.input(edge.target, edge.target_port)?;
+            match id {
+                Some(id) => builder.graph.insert_link(Link { id, start, end }),
+                None => {
+                    builder.graph.add_link(start, end);
+                }
+            }
+        }
+
+        Ok((builder.graph, layout))
+    }
+
+    fn write_graphml(&self, positions: &HashMap<NodeId, [f32; 2]>) -> String {
+        let mut xml = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="label" for="all" attr.name="label" attr.type="string"/>
+  <key id="x" for="node" attr.name="x" attr.type="double"/>
+  <key id="y" for="node" attr.name="y" attr.type="double"/>
+  <key id="direction" for="port" attr.name="direction" attr.type="string"/>
+  <key id="shape" for="port" attr.name="shape" attr.type="string"/>
+  <graph id="G" edgedefault="directed">
+"#,
+        );
+        let mut owners: HashMap<i32, i32> = HashMap::new();
+        for node in self.nodes() {
+            let id: i32 = node.id.into();
+            writeln!(xml, r#"    <node id="n{}">"#, id).unwrap();
+            writeln!(
+                xml,
+                r#"      <data key="label">{}</data>"#,
+                escape(&node.title)
+            )
+            .unwrap();
+            if let Some([x, y]) = positions.get(&node.id) {
+                writeln!(xml, r#"      <data key="x">{}</data>"#, x).unwrap();
+                writeln!(xml, r#"      <data key="y">{}</data>"#, y).unwrap();
+            }
+            let inputs = node
+                .inputs
+                .iter()
+                .map(|pin| (pin.id.into(), "input", &pin.label, pin.shape));
+            let outputs = node
+                .outputs
+                .iter()
+                .map(|pin| (pin.id.into(), "output", &pin.label, pin.shape));
+            for (pin, direction, label, shape) in inputs.chain(outputs) {
+                let pin: i32 = pin;
+                owners.insert(pin, id);
+                writeln!(xml, r#"      <port name="p{}">"#, pin).unwrap();
+                writeln!(xml, r#"        <data key="direction">{}</data>"#, direction).unwrap();
+                writeln!(xml, r#"        <data key="label">{}</data>"#, escape(label)).unwrap();
+                writeln!(xml, r#"        <data key="shape">{:?}</data>"#, shape).unwrap();
+                xml.push_str("      </port>\n");
+            }
+            xml.push_str("    </node>\n");
+        }
+        for link in self.links() {
+            let (id, start, end): (i32, i32, i32) =
+                (link.id.into(), link.start.into(), link.end.into());
+            if let (Some(source), Some(target)) = (owners.get(&start), owners.get(&end)) {
+                writeln!(
+                    xml,
+                    r#"    <edge id="e{}" source="n{}" target="n{}" sourceport="p{}" targetport="p{}"/>"#,
+                    id, source, target, start, end
+                )
+                .unwrap();
+            }
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Direction {
+    Input,
+    Output,
+}
+
+#[derive(Debug)]
+struct XmlPort {
+    name: String,
+    label: String,
+    direction: Option<Direction>,
+    shape: PinShape,
+}
+
+#[derive(Debug, Default)]
+struct XmlNode {
+    id: String,
+    title: String,
+    x: Option<f32>,
+    y: Option<f32>,
+    ports: Vec<XmlPort>,
+}
+
+#[derive(Debug)]
+struct XmlEdge {
+    id: Option<String>,
+    source: String,
+    target: String,
+    source_port: Option<String>,
+    target_port: Option<String>,
+}
+
+/// content of the file before any ids are created
+#[derive(Debug, Default)]
+struct Parsed {
+    /// `attr.name` of each `key` by its id
+    keys: HashMap<String, String>,
+    nodes: Vec<XmlNode>,
+    edges: Vec<XmlEdge>,
+    in_node: bool,
+    in_port: bool,
+    /// `attr.name` of the `data` element which is open
+    data: Option<String>,
+}
+
+fn parse(xml: &str) -> Result<Parsed, GraphMlError> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut parsed = Parsed::default();
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => parsed.open(&element, false)?,
+            Event::Empty(element) => parsed.open(&element, true)?,
+            Event::Text(text) => parsed.value(&text.unescape()?),
+            Event::CData(text) => parsed.value(&String::from_utf8_lossy(&text)),
+            Event::End(element) => match element.local_name().as_ref() {
+                b"node" => parsed.in_node = false,
+                b"port" => parsed.in_port = false,
+                b"data" => parsed.data = None,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(parsed)
+}
+
+impl Parsed {
+    fn open(&mut self, element: &BytesStart<'_>, empty: bool) -> Result<(), GraphMlError> {
+        match element.local_name().as_ref() {
+            b"key" => {
+                let id = required(element, "id")?;
+                let name = attribute(element, "attr.name")?.unwrap_or_else(|| id.clone());
+                self.keys.insert(id, name);
+            }
+            b"node" => {
+                self.nodes.push(XmlNode {
+                    id: required(element, "id")?,
+                    ..XmlNode::default()
+                });
+                self.in_node = !empty;
+            }
+            b"port" if self.in_node => {
+                let port = XmlPort {
+                    name: required(element, "name")?,
+                    label: String::new(),
+                    direction: None,
+                    shape: PinShape::CircleFilled,
+                };
+                if let Some(node) = self.nodes.last_mut() {
+                    node.ports.push(port);
+                }
+                self.in_port = !empty;
+            }
+            b"edge" => self.edges.push(XmlEdge {
+                id: attribute(element, "id")?,
+                source: required(element, "source")?,
+                target: required(element, "target")?,
+                source_port: attribute(element, "sourceport")?,
+                target_port: attribute(element, "targetport")?,
+            }),
+            b"data" if !empty => {
+                let key = required(element, "key")?;
+                self.data = Some(self.keys.get(&key).cloned().unwrap_or(key));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// text of the open `data` element, values of edges and the graph are ignored
+    fn value(&mut self, value: &str) {
+        let name = match &self.data {
+            Some(name) if self.in_node => name.as_str(),
+            _ => return,
+        };
+        let node = match self.nodes.last_mut() {
+            Some(node) => node,
+            None => return,
+        };
+        if self.in_port {
+            if let Some(port) = node.ports.last_mut() {
+                match name {
+                    "label" => port.label = value.to_string(),
+                    "direction" if value == "input" => port.direction = Some(Direction::Input),
+                    "direction" if value == "output" => port.direction = Some(Direction::Output),
+                    "shape" => port.shape = parse_shape(value).unwrap_or(port.shape),
+                    _ => {}
+                }
+            }
+        } else {
+            match name {
+                "label" => node.title = value.to_string(),
+                "x" => node.x = value.trim().parse().ok(),
+                "y" => node.y = value.trim().parse().ok(),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn attribute(element: &BytesStart<'_>, name: &str) -> Result<Option<String>, GraphMlError> {
+    match element.try_get_attribute(name)? {
+        Some(attribute) => Ok(Some(attribute.unescape_value()?.into_owned())),
+        None => Ok(None),
+    }
+}
+
+fn required(element: &BytesStart<'_>, name: &'static str) -> Result<String, GraphMlError> {
+    attribute(element, name)?.ok_or(GraphMlError::MissingAttribute(name))
+}
+
+fn parse_shape(name: &str) -> Option<PinShape> {
+    Some(match name {
+        "Circle" => PinShape::Circle,
+        "CircleFilled" => PinShape::CircleFilled,
+        "Triangle" => PinShape::Triangle,
+        "TriangleFilled" => PinShape::TriangleFilled,
+        "Quad" => PinShape::Quad,
+        "QuadFilled" => PinShape::QuadFilled,
+        _ => return None,
+    })
+}
+
+/// ids of the file which have the form written by [Graph::to_graphml],
+/// each id is only kept for the first element which has it and removed once it is used
+#[derive(Debug, Default)]
+struct KeptIds {
+    nodes: HashMap<String, NodeId>,
+    /// pins with a direction by node and port name
+    pins: HashMap<(String, String), i32>,
+    links: HashMap<String, LinkId>,
+    /// highest kept id of any kind, -1 if there is none
+    highest: i32,
+}
+
+impl KeptIds {
+    fn of(parsed: &Parsed) -> Self {
+        let mut kept = KeptIds {
+            highest: -1,
+            ..KeptIds::default()
+        };
+        let (mut nodes, mut pins, mut links) = (HashSet::new(), HashSet::new(), HashSet::new());
+
+        for node in &parsed.nodes {
+            if let Some(id) = numbered(&node.id, 'n').filter(|id| nodes.insert(*id)) {
+                kept.nodes.insert(node.id.clone(), NodeId { id });
+                kept.highest = kept.highest.max(id);
+            }
+            for port in node.ports.iter().filter(|port| port.direction.is_some()) {
+                if let Some(id) = numbered(&port.name, 'p').filter(|id| pins.insert(*id)) {
+                    kept.pins.insert((node.id.clone(), port.name.clone()), id);
+                    kept.highest = kept.highest.max(id);
+                }
+            }
+        }
+        for edge in &parsed.edges {
+            let name = match &edge.id {
+                Some(name) => name,
+                None => continue,
+            };
+            if let Some(id) = numbered(name, 'e').filter(|id| links.insert(*id)) {
+                kept.links.insert(name.clone(), LinkId { id });
+                kept.highest = kept.highest.max(id);
+            }
+        }
+        kept
+    }
+}
+
+/// the number of an id like `n3`
+fn numbered(id: &str, prefix: char) -> Option<i32> {
+    id.strip_prefix(prefix)?.parse().ok().filter(|id| *id >= 0)
+}
+
+/// graph which is filled from the parsed nodes, the pins are looked up by node and port name
+struct Builder<N> {
+    graph: Graph<N>,
+    nodes: HashMap<String, NodeId>,
+    inputs: HashMap<(String, String), InputPinId>,
+    outputs: HashMap<(String, String), OutputPinId>,
+    undirected: HashMap<(String, String), XmlPort>,
+}
+
+impl<N> Builder<N> {
+    /// input pin with an id from the file
+    fn insert_input(&mut self, node: NodeId, id: InputPinId, label: String, shape: PinShape) {
+        let node = self.graph.node_mut(node).expect("node was just added");
+        node.inputs.push(Pin {
+            id,
+            label,
+            shape,
+            capacity: Capacity::default(),
+        });
+    }
+
+    /// output pin with an id from the file
+    fn insert_output(&mut self, node: NodeId, id: OutputPinId, label: String, shape: PinShape) {
+        let node = self.graph.node_mut(node).expect("node was just added");
+        node.outputs.push(Pin {
+            id,
+            label,
+            shape,
+            capacity: Capacity::default(),
+        });
+    }
+
+    /// output pin for the source of an edge, created if it does not exist yet
+    fn output(&mut self, node: String, port: Option<String>) -> Result<OutputPinId, GraphMlError> {
+        let id = *self
+            .nodes
+            .get(&node)
+            .ok_or_else(|| GraphMlError::UnknownNode(node.clone()))?;
+        // edges without port share the pin with the empty name
+        let key = (node, port.unwrap_or_default());
+        if let Some(pin) = self.outputs.get(&key) {
+            return Ok(*pin);
+        }
+        let (label, shape) = self.undirected_port(&key);
+        let pin = self.graph.add_output(id, label, shape);
+        self.outputs.insert(key, pin);
+        Ok(pin)
+    }
+
+    /// input pin for the target of an edge, created if it does not exist yet
+    fn input(&mut self, node: String, port: Option<String>) -> Result<InputPinId, GraphMlError> {
+        let id = *self
+            .nodes
+            .get(&node)
+            .ok_or_else(|| GraphMlError::UnknownNode(node.clone()))?;
+        let key = (node, port.unwrap_or_default());
+        if let Some(pin) = self.inputs.get(&key) {
+            return Ok(*pin);
+        }
+        let (label, shape) = self.undirected_port(&key);
+        let pin = self.graph.add_input(id, label, shape);
+        self.inputs.insert(key, pin);
+        Ok(pin)
+    }
+
+    fn undirected_port(&self, key: &(String, String)) -> (String, PinShape) {
+        match self.undirected.get(key) {
+            Some(port) => (port.label.clone(), port.shape),
+            None => (key.1.clone(), PinShape::CircleFilled),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+
+    /// id, label and shape of every pin, inputs first
+    fn pins(node: &Node<()>) -> Vec<(i32, &str, PinShape)> {
+        let inputs = node
+            .inputs
+            .iter()
+            .map(|pin| (pin.id.into(), pin.label.as_str(), pin.shape));
+        let outputs = node
+            .outputs
+            .iter()
+            .map(|pin| (pin.id.into(), pin.label.as_str(), pin.shape));
+        inputs.chain(outputs).collect()
+    }
+
+    #[test]
+    fn round_trip_keeps_ids_links_and_escaped_names() {
+        let mut graph: Graph<()> = Graph::new();
+        let source = graph.add_node(r#"<source> & "quotes""#, ());
+        let value = graph.add_output(source, "a < b", PinShape::Triangle);
+        let sink = graph.add_node("it's > 1", ());
+        let input = graph.add_input(sink, "&amp;", PinShape::QuadFilled);
+        let other = graph.add_input(sink, "plain", PinShape::Circle);
+        graph.add_link(value, input);
+        graph.add_link(value, other);
+        let layout = EditorLayout {
+            panning: [0.0, 0.0],
+            positions: vec![(source, [10.0, -20.5]), (sink, [300.0, 40.0])],
+        };
+
+        let xml = graph.to_graphml_with_layout(&layout);
+        let (imported, imported_layout) = Graph::<()>::from_graphml(&xml).unwrap();
+
+        assert_eq!(imported.nodes().len(), graph.nodes().len());
+        for (node, other) in graph.nodes().iter().zip(imported.nodes()) {
+            assert_eq!(node.id, other.id);
+            assert_eq!(node.title, other.title);
+            assert_eq!(pins(node), pins(other));
+        }
+        assert_eq!(imported.links(), graph.links());
+        assert_eq!(imported_layout.positions, layout.positions);
+    }
+
+    #[test]
+    fn ids_with_gaps_are_kept() {
+        let mut graph: Graph<()> = Graph::new();
+        let removed = graph.add_node("removed", ());
+        graph.add_output(removed, "gone", PinShape::Circle);
+        let source = graph.add_node("source", ());
+        let value = graph.add_output(source, "value", PinShape::Circle);
+        let sink = graph.add_node("sink", ());
+        let input = graph.add_input(sink, "input", PinShape::Circle);
+        let first = graph.add_link(value, input);
+        graph.remove_link(first);
+        let link = graph.add_link(value, input);
+        graph.remove_node(removed);
+
+        let (mut imported, _) = Graph::<()>::from_graphml(&graph.to_graphml()).unwrap();
+        let ids: Vec<NodeId> = imported.nodes().iter().map(|node| node.id).collect();
+        assert_eq!(ids, vec![source, sink]);
+        assert_eq!(imported.node(source).unwrap().outputs[0].id, value);
+        assert_eq!(imported.node(sink).unwrap().inputs[0].id, input);
+        assert_eq!(imported.links(), graph.links());
+        assert_eq!(imported.link(link).map(|link| link.start), Some(value));
+
+        // new elements continue after the kept ids
+        let added = imported.add_node("added", ());
+        assert!(ids.iter().all(|id| id.id < added.id));
+    }
+
+    #[test]
+    fn foreign_ids_get_fresh_ones() {
+        let xml = r#"<graphml>
+  <graph edgedefault="directed">
+    <node id="n5"/>
+    <node id="a"/>
+    <node id="n5"/>
+    <edge source="n5" target="a"/>
+  </graph>
+</graphml>"#;
+        let (graph, _) = Graph::<()>::from_graphml(xml).unwrap();
+        let ids: Vec<i32> = graph.nodes().iter().map(|node| node.id.id).collect();
+        assert_eq!(ids, vec![5, 6, 7]);
+        assert_eq!(graph.links().len(), 1);
+    }
+}
@@ -0,0 +1,120 @@
+/*!
+Group frames (comment boxes) drawn behind a set of nodes.
+
+The frame is computed from the current positions and sizes of its nodes every frame,
+dragging its title bar moves all contained nodes together.
+*/
+
+use crate::{CoordinateSystem, NodeId, ScopeEditor};
+use imgui::{im_str, Ui};
+
+/// titled rectangle around some nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    /// text in the title bar
+    pub title: String,
+    /// nodes inside the frame
+    pub nodes: Vec<NodeId>,
+    /// background of the frame
+    pub color: [f32; 4],
+    /// background of the title bar
+    pub title_color: [f32; 4],
+    /// space between the nodes and the border of the frame
+    pub padding: f32,
+}
+
+impl Group {
+    /// frame with default colors
+    pub fn new<S: Into<String>>(title: S, nodes: Vec<NodeId>) -> Self {
+        Self {
+            title: title.into(),
+            nodes,
+            color: [0.3, 0.3, 0.4, 0.3],
+            title_color: [0.3, 0.3, 0.4, 0.8],
+            padding: 10.0,
+        }
+    }
+
+    /// screen space rectangle around the nodes including the title bar,
+    /// `None` if the group is empty
+    ///
+    /// uses the sizes from the last time the nodes were drawn
+    pub fn screen_rect(&self, ui: &Ui) -> Option<([f32; 2], [f32; 2])> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut min = [f32::MAX; 2];
+        let mut max = [f32::MIN; 2];
+        for id in &self.nodes {
+            let pos = id.get_position(CoordinateSystem::ScreenSpace);
+            let size = id.get_dimensions();
+            min = [min[0].min(pos.x), min[1].min(pos.y)];
+            max = [max[0].max(pos.x + size.x), max[1].max(pos.y + size.y)];
+        }
+        Some((
+            [
+                min[0] - self.padding,
+                min[1] - self.padding - self.title_height(ui),
+            ],
+            [max[0] + self.padding, max[1] + self.padding],
+        ))
+    }
+
+    fn title_height(&self, ui: &Ui) -> f32 {
+        ui.text_line_height() + self.padding
+    }
+
+    /// draw the frame and move the nodes if the title bar is dragged
+    ///
+    /// has to be called before the nodes are added so the frame ends up behind them,
+    /// returns true while the group is being dragged
+    pub fn draw(&self, ui: &Ui, _: &ScopeEditor<'_>) -> bool {
+        let (min, max) = match self.screen_rect(ui) {
+            Some(rect) => rect,
+            None => return false,
+        };
+        let title_max = [max[0], min[1] + self.title_height(ui)];
+
+        // an invisible button over the title bar to pick up the drag
+        let id = ui.push_id(self.nodes[0].id);
+        ui.set_cursor_screen_pos(min);
+        ui.invisible_button(
+            im_str!("##group_title"),
+            [max[0] - min[0], title_max[1] - min[1]],
+        );
+        let dragging = ui.is_item_active();
+        id.pop(ui);
+
+        let draw_list = ui.get_window_draw_list();
+        draw_list
+            .add_rect(min, max, self.color)
+            .filled(true)
+            .rounding(4.0)
+            .build();
+        draw_list
+            .add_rect(min, title_max, self.title_color)
+            .filled(true)
+            .rounding(4.0)
+            .round_bot_left(false)
+            .round_bot_right(false)
+            .build();
+        draw_list.add_text(
+            [min[0] + self.padding, min[1] + self.padding / 2.0],
+            [1.0, 1.0, 1.0, 1.0],
+            &self.title,
+        );
+
+        if dragging {
+            let [dx, dy] = ui.io().mouse_delta;
+            if dx != 0.0 || dy != 0.0 {
+                for id in &self.nodes {
+                    let pos = id.get_position(CoordinateSystem::GridSpace);
+                    id.set_position(pos.x + dx, pos.y + dy, CoordinateSystem::GridSpace);
+                }
+            }
+        }
+
+        dragging
+    }
+}
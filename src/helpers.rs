@@ -1,18 +1,214 @@
-use crate::EditorContext;
+use crate::{CoordinateSystem, EditorContext, NodeId, ScopeNone};
 use imnodes_sys as sys;
 
 impl EditorContext {
     /// EditorContextGetPanning
     pub fn get_panning(&self) -> sys::ImVec2 {
+        let _previous = self.make_current();
         let mut position = sys::ImVec2 { x: 0.0, y: 0.0 };
         unsafe { sys::imnodes_EditorContextGetPanning(&mut position as _) };
         position
     }
     /// EditorContextResetPanning
     pub fn reset_panning(&self, pos: sys::ImVec2) {
+        let _previous = self.make_current();
         unsafe { sys::imnodes_EditorContextResetPanning(pos) };
     }
 
+    /// EditorContextMoveToNode
+    /// pan this editor so that the node is in view, see [NodeId::move_editor_to]
+    pub fn move_to_node(&self, id: NodeId) {
+        let _previous = self.make_current();
+        id.move_editor_to();
+    }
+
+    /// pan smoothly until the node is in the center of the canvas,
+    /// the panning changes in [EditorContext::tick] which has to be called every frame
+    ///
+    /// uses the position and size of the node and the canvas of the last frame
+    pub fn animate_to_node(&self, id: NodeId, seconds: f32) {
+        let _previous = self.make_current();
+        let to = match self.nodes_rect(&[id]) {
+            Some((min, max)) => self.panning_to_center(min, max),
+            None => return,
+        };
+        let from = self.get_panning();
+        self.pan_animation.set(Some(PanAnimation {
+            from: [from.x, from.y],
+            to,
+            seconds,
+            elapsed: 0.0,
+        }));
+    }
+
+    /// pan so that the bounding box of the nodes is in the center of the canvas,
+    /// e.g. all nodes of a graph for "frame all" or [ScopeNone::frame_selection]
+    ///
+    /// imnodes has no zoom, so a box which is larger than the canvas is only centered.
+    /// Uses the positions and sizes of the nodes and the canvas of the last frame
+    pub fn frame_nodes(&self, nodes: &[NodeId]) {
+        if let Some((min, max)) = self.nodes_rect(nodes) {
+            let [x, y] = self.panning_to_center(min, max);
+            self.pan_animation.set(None);
+            let _previous = self.make_current();
+            self.reset_panning(sys::ImVec2 { x, y });
+        }
+    }
+
+    /// grid space bounding box of the nodes, `None` for no nodes
+    fn nodes_rect(&self, nodes: &[NodeId]) -> Option<([f32; 2], [f32; 2])> {
+        let _previous = self.make_current();
+        nodes.iter().fold(None, |rect, id| {
+            let position = id.get_position(CoordinateSystem::GridSpace);
+            let size = id.get_dimensions();
+            let (min, max) = (
+                [position.x, position.y],
+                [position.x + size.x, position.y + size.y],
+            );
+            Some(match rect {
+                Some((rect_min, rect_max)) => (
+                    [min[0].min(rect_min[0]), min[1].min(rect_min[1])],
+                    [max[0].max(rect_max[0]), max[1].max(rect_max[1])],
+                ),
+                None => (min, max),
+            })
+        })
+    }
+
+    /// panning which puts the center of a grid space rect into the center of the canvas
+    fn panning_to_center(&self, min: [f32; 2], max: [f32; 2]) -> [f32; 2] {
+        let [width, height] = self.canvas_size();
+        [
+            0.5 * (width - min[0] - max[0]),
+            0.5 * (height - min[1] - max[1]),
+        ]
+    }
+
+    /// advance the animation started by [EditorContext::animate_to_node],
+    /// call it once per frame before [editor](crate::editor)
+    ///
+    /// returns whether the editor is still moving
+    pub fn tick(&self, ui: &imgui::Ui) -> bool {
+        let mut animation = match self.pan_animation.get() {
+            Some(animation) => animation,
+            None => return false,
+        };
+        animation.elapsed += ui.io().delta_time;
+        let t = if animation.seconds > 0.0 {
+            (animation.elapsed / animation.seconds).min(1.0)
+        } else {
+            1.0
+        };
+        // ease in and out so the start and the end are not abrupt
+        let eased = t * t * (3.0 - 2.0 * t);
+        let [from_x, from_y] = animation.from;
+        let [to_x, to_y] = animation.to;
+        let _previous = self.make_current();
+        self.reset_panning(sys::ImVec2 {
+            x: from_x + (to_x - from_x) * eased,
+            y: from_y + (to_y - from_y) * eased,
+        });
+
+        let moving = t < 1.0;
+        self.pan_animation
+            .set(if moving { Some(animation) } else { None });
+        moving
+    }
+
+    /// screen space position of the top left corner of the canvas,
+    /// captured in BeginNodeEditor of the last frame
+    pub fn canvas_origin(&self) -> [f32; 2] {
+        self.canvas_origin.get()
+    }
+
+    /// size of the canvas in pixels, captured in BeginNodeEditor of the last frame
+    pub fn canvas_size(&self) -> [f32; 2] {
+        self.canvas_size.get()
+    }
+
+    /// is a screen space point inside the canvas, e.g. to only handle clicks on the editor
+    pub fn canvas_contains(&self, [x, y]: [f32; 2]) -> bool {
+        let [origin_x, origin_y] = self.canvas_origin();
+        let [width, height] = self.canvas_size();
+        x >= origin_x && y >= origin_y && x < origin_x + width && y < origin_y + height
+    }
+
+    /// grid space rect `(min, max)` of the part of the grid which was visible in the last frame,
+    /// e.g. to skip nodes which are off screen
+    pub fn visible_rect(&self) -> ([f32; 2], [f32; 2]) {
+        let _previous = self.make_current();
+        let min = self.screen_to_grid(self.canvas_origin());
+        let [width, height] = self.canvas_size();
+        (min, [min[0] + width, min[1] + height])
+    }
+
+    /// editor space position of a point on the screen,
+    /// based on where the canvas was drawn in the last frame
+    pub fn screen_to_editor(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let [origin_x, origin_y] = self.canvas_origin.get();
+        [x - origin_x, y - origin_y]
+    }
+
+    /// screen space position of a point in the editor,
+    /// based on where the canvas was drawn in the last frame
+    pub fn editor_to_screen(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let [origin_x, origin_y] = self.canvas_origin.get();
+        [x + origin_x, y + origin_y]
+    }
+
+    /// grid space position of a point on the screen, e.g. to spawn a node at the mouse
+    pub fn screen_to_grid(&self, point: [f32; 2]) -> [f32; 2] {
+        let _previous = self.make_current();
+        let [x, y] = self.screen_to_editor(point);
+        let panning = self.get_panning();
+        [x - panning.x, y - panning.y]
+    }
+
+    /// screen space position of a point on the grid, e.g. for custom drawing on the canvas
+    pub fn grid_to_screen(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let _previous = self.make_current();
+        let panning = self.get_panning();
+        self.editor_to_screen([x + panning.x, y + panning.y])
+    }
+
+    /// convert a point between any two [CoordinateSystem]s
+    pub fn convert(
+        &self,
+        point: [f32; 2],
+        from: CoordinateSystem,
+        to: CoordinateSystem,
+    ) -> [f32; 2] {
+        let _previous = self.make_current();
+        let screen = match from {
+            CoordinateSystem::ScreenSpace => point,
+            CoordinateSystem::EditorSpace => self.editor_to_screen(point),
+            CoordinateSystem::GridSpace => self.grid_to_screen(point),
+        };
+        match to {
+            CoordinateSystem::ScreenSpace => screen,
+            CoordinateSystem::EditorSpace => self.screen_to_editor(screen),
+            CoordinateSystem::GridSpace => self.screen_to_grid(screen),
+        }
+    }
+
+    /// grid space positions of the nodes in the same order, e.g. to save a layout
+    pub fn capture_positions(&self, nodes: &[NodeId]) -> Vec<sys::ImVec2> {
+        let _previous = self.make_current();
+        nodes
+            .iter()
+            .map(|id| id.get_position(CoordinateSystem::GridSpace))
+            .collect()
+    }
+
+    /// move nodes to grid space positions, e.g. to restore a layout from
+    /// [EditorContext::capture_positions]
+    pub fn apply_positions(&self, positions: &[(NodeId, sys::ImVec2)]) {
+        let _previous = self.make_current();
+        for (id, position) in positions {
+            id.set_position(position.x, position.y, CoordinateSystem::GridSpace);
+        }
+    }
+
     /// ClearNodeSelection
     pub fn clear_node_selection(&self) {
         unsafe { sys::imnodes_ClearNodeSelection() };
@@ -24,7 +220,23 @@ impl EditorContext {
     }
 }
 
+impl ScopeNone<'_> {
+    /// [EditorContext::frame_nodes] with the selected nodes, does nothing without a selection
+    pub fn frame_selection(&self) {
+        self.guard.context.frame_nodes(&self.selected_nodes());
+    }
+}
+
 /// IsAttributeActive
 pub fn is_last_attribute_active() -> bool {
     unsafe { sys::imnodes_IsAttributeActive() }
 }
+
+/// state of [EditorContext::animate_to_node]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct PanAnimation {
+    from: [f32; 2],
+    to: [f32; 2],
+    seconds: f32,
+    elapsed: f32,
+}
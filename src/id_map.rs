@@ -0,0 +1,276 @@
+/*!
+Ids for applications which already have their own keys, like UUIDs or names.
+
+imnodes only knows `i32` ids. [IdMap] derives the id from a hash of the key, so the same key
+gets the same id in every run, and falls back to the next free id on collisions.
+Keys are stored in both directions, so events from imnodes can be mapped back to the key.
+
+```ignore
+let mut ids = IdMap::new();
+for node in &document.nodes {
+    editor.add_node(ids.node(&node.uuid), |mut scope| { ... });
+}
+if let Some(hovered) = scope.get_hovered_node() {
+    let uuid = ids.node_key(hovered);
+}
+```
+*/
+
+use crate::{AttributeId, InputPinId, LinkId, NodeId, OutputPinId, PinId};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+
+/// FNV-1a, unlike the std hasher its output is fixed
+struct StableHasher(u64);
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// ids of one kind, imnodes only requires uniqueness among nodes, among pins/ attributes
+/// and among links
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(from = "HashMap<i32, K>", into = "HashMap<i32, K>")
+)]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "K: Serialize + Clone + Hash + Eq",
+        deserialize = "K: Deserialize<'de> + Clone + Hash + Eq"
+    ))
+)]
+struct KeySpace<K: Clone + Hash + Eq> {
+    ids: HashMap<K, i32>,
+    keys: HashMap<i32, K>,
+}
+
+impl<K: Clone + Hash + Eq> From<HashMap<i32, K>> for KeySpace<K> {
+    fn from(keys: HashMap<i32, K>) -> Self {
+        Self {
+            ids: keys.iter().map(|(id, key)| (key.clone(), *id)).collect(),
+            keys,
+        }
+    }
+}
+
+impl<K: Clone + Hash + Eq> From<KeySpace<K>> for HashMap<i32, K> {
+    fn from(space: KeySpace<K>) -> Self {
+        space.keys
+    }
+}
+
+impl<K: Clone + Hash + Eq> KeySpace<K> {
+    fn new() -> Self {
+        Self {
+            ids: HashMap::new(),
+            keys: HashMap::new(),
+        }
+    }
+
+    fn id(&mut self, key: &K) -> i32 {
+        if let Some(id) = self.ids.get(key) {
+            return *id;
+        }
+
+        let mut hasher = StableHasher(0xcbf2_9ce4_8422_2325);
+        key.hash(&mut hasher);
+        // imnodes uses negative ids to signal "no id"
+        let mut id = (hasher.finish() & 0x7fff_ffff) as i32;
+        while self.keys.contains_key(&id) {
+            id = (id + 1) & 0x7fff_ffff;
+        }
+        self.ids.insert(key.clone(), id);
+        self.keys.insert(id, key.clone());
+        id
+    }
+
+    fn remove(&mut self, key: &K) -> Option<i32> {
+        let id = self.ids.remove(key)?;
+        self.keys.remove(&id);
+        Some(id)
+    }
+}
+
+/// two way mapping between application keys and imnodes ids
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "K: Serialize + Clone + Hash + Eq",
+        deserialize = "K: Deserialize<'de> + Clone + Hash + Eq"
+    ))
+)]
+pub struct IdMap<K: Clone + Hash + Eq> {
+    nodes: KeySpace<K>,
+    pins: KeySpace<K>,
+    links: KeySpace<K>,
+}
+
+impl<K: Clone + Hash + Eq> Default for IdMap<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone + Hash + Eq> IdMap<K> {
+    /// no keys yet
+    pub fn new() -> Self {
+        Self {
+            nodes: KeySpace::new(),
+            pins: KeySpace::new(),
+            links: KeySpace::new(),
+        }
+    }
+
+    /// id of the node with this key, assigned on first use
+    pub fn node(&mut self, key: &K) -> NodeId {
+        NodeId {
+            id: self.nodes.id(key),
+        }
+    }
+
+    /// id of the input pin with this key, assigned on first use
+    pub fn input_pin(&mut self, key: &K) -> InputPinId {
+        InputPinId {
+            id: self.pins.id(key),
+        }
+    }
+
+    /// id of the output pin with this key, assigned on first use
+    pub fn output_pin(&mut self, key: &K) -> OutputPinId {
+        OutputPinId {
+            id: self.pins.id(key),
+        }
+    }
+
+    /// id of the static attribute with this key, assigned on first use
+    ///
+    /// attributes share their ids with the pins, so a key must not be used for both
+    pub fn attribute(&mut self, key: &K) -> AttributeId {
+        AttributeId {
+            id: self.pins.id(key),
+        }
+    }
+
+    /// id of the link with this key, assigned on first use
+    pub fn link(&mut self, key: &K) -> LinkId {
+        LinkId {
+            id: self.links.id(key),
+        }
+    }
+
+    /// key of a node id
+    pub fn node_key(&self, id: NodeId) -> Option<&K> {
+        self.nodes.keys.get(&id.id)
+    }
+
+    /// key of an input or output pin id
+    pub fn pin_key<P: Into<PinId>>(&self, id: P) -> Option<&K> {
+        self.pins.keys.get(&id.into().id)
+    }
+
+    /// key of an attribute id
+    pub fn attribute_key(&self, id: AttributeId) -> Option<&K> {
+        self.pins.keys.get(&id.id)
+    }
+
+    /// key of a link id
+    pub fn link_key(&self, id: LinkId) -> Option<&K> {
+        self.links.keys.get(&id.id)
+    }
+
+    /// forget a node key so its id can be reused
+    pub fn remove_node(&mut self, key: &K) -> Option<NodeId> {
+        self.nodes.remove(key).map(|id| NodeId { id })
+    }
+
+    /// forget a pin or attribute key so its id can be reused
+    pub fn remove_pin(&mut self, key: &K) -> Option<PinId> {
+        self.pins.remove(key).map(|id| PinId { id })
+    }
+
+    /// forget a link key so its id can be reused
+    pub fn remove_link(&mut self, key: &K) -> Option<LinkId> {
+        self.links.remove(key).map(|id| LinkId { id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_key_gets_the_same_id() {
+        let mut ids = IdMap::new();
+        let first = ids.node(&"first");
+        ids.node(&"second");
+        assert_eq!(ids.node(&"first"), first);
+
+        // independent of the order keys were added in
+        let mut other = IdMap::new();
+        other.node(&"second");
+        assert_eq!(other.node(&"first"), first);
+        assert_eq!(ids.node_key(first), Some(&"first"));
+        assert!(first.id >= 0);
+    }
+
+    #[test]
+    fn kinds_have_their_own_ids() {
+        let mut ids = IdMap::new();
+        let node = ids.node(&"key");
+        let link = ids.link(&"key");
+        assert_eq!(node.id, link.id);
+        assert_eq!(ids.link_key(link), Some(&"key"));
+
+        // inputs and outputs share one space
+        let input = ids.input_pin(&"in");
+        let output = ids.output_pin(&"out");
+        assert_ne!(input.id, output.id);
+        assert_eq!(ids.pin_key(output), Some(&"out"));
+    }
+
+    #[test]
+    fn collision_takes_the_next_free_id() {
+        let hashed = KeySpace::new().id(&"key");
+        let mut space = KeySpace::from(HashMap::from([(hashed, "taken")]));
+        assert_eq!(space.id(&"key"), hashed + 1);
+        assert_eq!(space.keys.get(&hashed), Some(&"taken"));
+
+        // the id is free again after the key was removed
+        assert_eq!(space.remove(&"taken"), Some(hashed));
+        assert_eq!(space.remove(&"key"), Some(hashed + 1));
+        assert_eq!(space.id(&"key"), hashed);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serde_round_trip_keeps_ids() {
+        let mut ids = IdMap::new();
+        let node = ids.node(&"node".to_string());
+        let pin = ids.input_pin(&"pin".to_string());
+        let link = ids.link(&"link".to_string());
+
+        let json = serde_json::to_string(&ids).unwrap();
+        let mut loaded: IdMap<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.node_key(node).map(String::as_str), Some("node"));
+        assert_eq!(loaded.pin_key(pin).map(String::as_str), Some("pin"));
+        assert_eq!(loaded.link(&"link".to_string()), link);
+    }
+}
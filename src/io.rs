@@ -0,0 +1,71 @@
+use crate::{sys, Context};
+use std::{
+    ptr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// keyboard modifier used by the imnodes IO settings
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ModifierKey {
+    /// Ctrl
+    Ctrl,
+    /// Shift
+    Shift,
+    /// Alt
+    Alt,
+    /// Cmd/ Super/ Windows key
+    Super,
+}
+
+/// state of the modifiers owned by the crate, imnodes keeps a pointer to the key it reads,
+/// so pointing it into the imgui io would dangle once the imgui context is dropped
+static MODIFIERS: [AtomicBool; 4] = [
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+];
+
+impl ModifierKey {
+    /// the flag lives as long as the program, so imnodes can keep the pointer
+    fn as_ptr(self) -> *const bool {
+        MODIFIERS[self as usize].as_ptr()
+    }
+}
+
+/// copy the modifiers from imgui before imnodes reads them in the editor
+pub(crate) fn update_modifiers(io: &imgui::Io) {
+    for (key, pressed) in [
+        (ModifierKey::Ctrl, io.key_ctrl),
+        (ModifierKey::Shift, io.key_shift),
+        (ModifierKey::Alt, io.key_alt),
+        (ModifierKey::Super, io.key_super),
+    ] {
+        MODIFIERS[key as usize].store(pressed, Ordering::Relaxed);
+    }
+}
+
+fn get_io() -> &'static mut sys::IO {
+    unsafe { &mut *sys::imnodes_GetIO() }
+}
+
+impl Context {
+    /// EmulateThreeButtonMouse
+    /// pan all editors with the left mouse button while the modifier is held,
+    /// `None` turns this off
+    pub fn set_emulate_three_button_mouse(&self, modifier: Option<ModifierKey>) -> &Self {
+        let settings = &mut get_io().emulate_three_button_mouse;
+        settings.enabled = modifier.is_some();
+        settings.modifier = modifier.map_or(ptr::null(), ModifierKey::as_ptr);
+        self
+    }
+
+    /// LinkDetachWithModifierClick
+    /// detach a link in any editor by clicking its pin while the modifier is held,
+    /// `None` turns this off
+    pub fn set_link_detach_with_modifier_click(&self, modifier: Option<ModifierKey>) -> &Self {
+        get_io().link_detach_with_modifier_click.modifier =
+            modifier.map_or(ptr::null(), ModifierKey::as_ptr);
+        self
+    }
+}
@@ -0,0 +1,382 @@
+/*!
+Automatic placement of nodes.
+
+The algorithms only work on [NodeId]s and edges between them, so they can be used with or
+without the retained [Graph]. `apply` variants compute the positions for a [Graph] and move
+its nodes with `SetNodeGridSpacePos`.
+*/
+
+use crate::{graph::Graph, EditorContext, ImVec2, NodeId};
+use std::collections::{HashMap, VecDeque};
+
+/// edges from the node owning the output pin to the node owning the input pin of every link
+pub fn graph_edges<N>(graph: &Graph<N>) -> Vec<(NodeId, NodeId)> {
+    graph
+        .links()
+        .iter()
+        .filter_map(|link| {
+            Some((
+                graph.output_owner(link.start)?,
+                graph.input_owner(link.end)?,
+            ))
+        })
+        .collect()
+}
+
+/// move nodes to grid space positions, [EditorContext::apply_positions] for the `[x, y]`
+/// positions computed here
+pub fn apply_positions(context: &EditorContext, positions: &[(NodeId, [f32; 2])]) {
+    let positions: Vec<(NodeId, ImVec2)> = positions
+        .iter()
+        .map(|(id, [x, y])| (*id, ImVec2 { x: *x, y: *y }))
+        .collect();
+    context.apply_positions(&positions);
+}
+
+/// layered (Sugiyama style) layout for directed graphs
+///
+/// nodes are put into columns so that links point from left to right,
+/// the nodes in each column are ordered to reduce crossing links.
+/// Cycles are broken arbitrarily.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LayeredLayout {
+    /// horizontal distance between the columns
+    pub layer_spacing: f32,
+    /// vertical distance between nodes in a column
+    pub node_spacing: f32,
+    /// number of barycenter passes used to order the nodes in each column
+    pub ordering_passes: usize,
+}
+
+impl Default for LayeredLayout {
+    fn default() -> Self {
+        Self {
+            layer_spacing: 250.0,
+            node_spacing: 120.0,
+            ordering_passes: 4,
+        }
+    }
+}
+
+impl LayeredLayout {
+    /// grid space position for every node
+    pub fn compute(&self, nodes: &[NodeId], edges: &[(NodeId, NodeId)]) -> Vec<(NodeId, [f32; 2])> {
+        let index: HashMap<NodeId, usize> =
+            nodes.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let edges: Vec<(usize, usize)> = edges
+            .iter()
+            .filter_map(|(from, to)| Some((*index.get(from)?, *index.get(to)?)))
+            .filter(|(from, to)| from != to)
+            .collect();
+
+        let mut layers = assign_layers(nodes.len(), &edges);
+        self.order_layers(&mut layers, &edges);
+
+        let mut positions = vec![];
+        for (column, layer) in layers.iter().enumerate() {
+            for (row, node) in layer.iter().enumerate() {
+                positions.push((
+                    nodes[*node],
+                    [
+                        column as f32 * self.layer_spacing,
+                        row as f32 * self.node_spacing,
+                    ],
+                ));
+            }
+        }
+        positions
+    }
+
+    /// lay out all nodes of the graph
+    pub fn apply<N>(&self, graph: &Graph<N>, context: &EditorContext) {
+        let nodes: Vec<NodeId> = graph.nodes().iter().map(|node| node.id).collect();
+        let positions = self.compute(&nodes, &graph_edges(graph));
+        apply_positions(context, &positions);
+    }
+
+    fn order_layers(&self, layers: &mut [Vec<usize>], edges: &[(usize, usize)]) {
+        for pass in 0..self.ordering_passes {
+            // alternate between sweeping down (looking at predecessors)
+            // and sweeping up (looking at successors)
+            let down = pass % 2 == 0;
+            let order: Vec<usize> = if down {
+                (1..layers.len()).collect()
+            } else {
+                (0..layers.len().saturating_sub(1)).rev().collect()
+            };
+
+            for layer in order {
+                let fixed = if down { layer - 1 } else { layer + 1 };
+                let rows: HashMap<usize, usize> = layers[fixed]
+                    .iter()
+                    .enumerate()
+                    .map(|(row, node)| (*node, row))
+                    .collect();
+
+                let mut keyed: Vec<(f32, usize)> = layers[layer]
+                    .iter()
+                    .enumerate()
+                    .map(|(row, node)| {
+                        let neighbours: Vec<usize> = edges
+                            .iter()
+                            .filter_map(|(from, to)| match down {
+                                true if to == node => rows.get(from).copied(),
+                                false if from == node => rows.get(to).copied(),
+                                _ => None,
+                            })
+                            .collect();
+                        let key = if neighbours.is_empty() {
+                            row as f32
+                        } else {
+                            neighbours.iter().sum::<usize>() as f32 / neighbours.len() as f32
+                        };
+                        (key, *node)
+                    })
+                    .collect();
+                keyed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+                layers[layer] = keyed.into_iter().map(|(_, node)| node).collect();
+            }
+        }
+    }
+}
+
+/// force directed (spring) layout, also works for cyclic and undirected graphs
+///
+/// instead of computing everything at once the simulation is advanced with [ForceLayout::step]
+/// a few iterations per frame, so the user can watch the graph settle
+#[derive(Debug, Clone)]
+pub struct ForceLayout {
+    /// preferred distance between connected nodes
+    pub spacing: f32,
+    /// total number of iterations until the layout is finished
+    pub iterations: usize,
+    /// iterations done by each [ForceLayout::step]
+    pub iterations_per_step: usize,
+    positions: Vec<(NodeId, [f32; 2])>,
+    edges: Vec<(usize, usize)>,
+    done: usize,
+}
+
+impl ForceLayout {
+    /// start from the given grid space positions
+    pub fn new(positions: &[(NodeId, [f32; 2])], edges: &[(NodeId, NodeId)]) -> Self {
+        let index: HashMap<NodeId, usize> = positions
+            .iter()
+            .enumerate()
+            .map(|(i, (id, _))| (*id, i))
+            .collect();
+        Self {
+            spacing: 200.0,
+            iterations: 300,
+            iterations_per_step: 5,
+            positions: positions.to_vec(),
+            edges: edges
+                .iter()
+                .filter_map(|(from, to)| Some((*index.get(from)?, *index.get(to)?)))
+                .filter(|(from, to)| from != to)
+                .collect(),
+            done: 0,
+        }
+    }
+
+    /// start from the current positions of the nodes in the graph
+    pub fn for_graph<N>(graph: &Graph<N>, context: &EditorContext) -> Self {
+        Self::new(
+            &graph.capture_layout(context).positions,
+            &graph_edges(graph),
+        )
+    }
+
+    /// current positions of the simulation
+    pub fn positions(&self) -> &[(NodeId, [f32; 2])] {
+        &self.positions
+    }
+
+    /// all iterations are done
+    pub fn is_finished(&self) -> bool {
+        self.done >= self.iterations
+    }
+
+    /// advance the simulation, returns false once it is finished
+    pub fn step(&mut self) -> bool {
+        for _ in 0..self.iterations_per_step {
+            if self.is_finished() {
+                break;
+            }
+            self.iterate();
+            self.done += 1;
+        }
+        !self.is_finished()
+    }
+
+    /// move the nodes in the editor to the current positions
+    pub fn apply(&self, context: &EditorContext) {
+        apply_positions(context, &self.positions);
+    }
+
+    /// one Fruchterman-Reingold iteration
+    fn iterate(&mut self) {
+        let k = self.spacing;
+        // nodes may move less the further the simulation is
+        let temperature = k * (1.0 - self.done as f32 / self.iterations as f32);
+        let count = self.positions.len();
+        let mut displacement = vec![[0.0f32; 2]; count];
+
+        for a in 0..count {
+            for b in (a + 1)..count {
+                let [dx, dy] = self.delta(a, b);
+                let distance = (dx * dx + dy * dy).sqrt();
+                let force = k * k / distance;
+                let (fx, fy) = (dx / distance * force, dy / distance * force);
+                displacement[a][0] += fx;
+                displacement[a][1] += fy;
+                displacement[b][0] -= fx;
+                displacement[b][1] -= fy;
+            }
+        }
+
+        for (from, to) in &self.edges {
+            let [dx, dy] = self.delta(*from, *to);
+            let distance = (dx * dx + dy * dy).sqrt();
+            let force = distance * distance / k;
+            let (fx, fy) = (dx / distance * force, dy / distance * force);
+            displacement[*from][0] -= fx;
+            displacement[*from][1] -= fy;
+            displacement[*to][0] += fx;
+            displacement[*to][1] += fy;
+        }
+
+        for ((_, [x, y]), [dx, dy]) in self.positions.iter_mut().zip(displacement) {
+            let length = (dx * dx + dy * dy).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature);
+                *x += dx / length * step;
+                *y += dy / length * step;
+            }
+        }
+    }
+
+    /// vector from `b` to `a`, never zero so nodes on top of each other get pushed apart
+    fn delta(&self, a: usize, b: usize) -> [f32; 2] {
+        let [ax, ay] = self.positions[a].1;
+        let [bx, by] = self.positions[b].1;
+        let (dx, dy) = (ax - bx, ay - by);
+        if dx == 0.0 && dy == 0.0 {
+            [(a as f32 - b as f32) * 0.1, 0.1]
+        } else {
+            [dx, dy]
+        }
+    }
+}
+
+/// longest path layering, a node in a cycle is placed as if its unvisited predecessors
+/// did not exist
+fn assign_layers(count: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut in_degree = vec![0; count];
+    for (_, to) in edges {
+        in_degree[*to] += 1;
+    }
+
+    let mut layer = vec![0; count];
+    let mut visited = vec![false; count];
+    let mut queue: VecDeque<usize> = (0..count).filter(|node| in_degree[*node] == 0).collect();
+
+    let mut remaining = count;
+    while remaining > 0 {
+        let node = match queue.pop_front() {
+            Some(node) => node,
+            // only cycles are left, break one of them
+            None => (0..count).find(|node| !visited[*node]).unwrap(),
+        };
+        if visited[node] {
+            continue;
+        }
+        visited[node] = true;
+        remaining -= 1;
+
+        for (from, to) in edges {
+            if *from == node && !visited[*to] {
+                layer[*to] = layer[*to].max(layer[node] + 1);
+                in_degree[*to] -= 1;
+                if in_degree[*to] == 0 {
+                    queue.push_back(*to);
+                }
+            }
+        }
+    }
+
+    let mut layers = vec![vec![]; layer.iter().max().map_or(0, |max| max + 1)];
+    for (node, layer) in layer.into_iter().enumerate() {
+        layers[layer].push(node);
+    }
+    layers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(count: i32) -> Vec<NodeId> {
+        (0..count).map(|id| NodeId { id }).collect()
+    }
+
+    /// column of every node, in the order of `nodes`
+    fn columns(nodes: &[NodeId], edges: &[(NodeId, NodeId)]) -> Vec<usize> {
+        let layout = LayeredLayout::default();
+        let positions = layout.compute(nodes, edges);
+        assert_eq!(positions.len(), nodes.len());
+        nodes
+            .iter()
+            .map(|node| {
+                let [x, _] = position_of(&positions, *node);
+                (x / layout.layer_spacing) as usize
+            })
+            .collect()
+    }
+
+    fn position_of(positions: &[(NodeId, [f32; 2])], node: NodeId) -> [f32; 2] {
+        let matching: Vec<_> = positions.iter().filter(|(id, _)| *id == node).collect();
+        assert_eq!(matching.len(), 1, "{:?} placed once", node);
+        matching[0].1
+    }
+
+    #[test]
+    fn links_point_to_later_layers() {
+        let n = ids(5);
+        // 0 -> 1 -> 2, 0 -> 2, 3 -> 2, 4 unconnected
+        let edges = [(n[1], n[2]), (n[0], n[1]), (n[0], n[2]), (n[3], n[2])];
+        assert_eq!(columns(&n, &edges), vec![0, 1, 2, 0, 0]);
+    }
+
+    #[test]
+    fn nodes_in_a_layer_do_not_overlap() {
+        let n = ids(4);
+        let edges = [(n[0], n[1]), (n[0], n[2]), (n[0], n[3])];
+        let layout = LayeredLayout::default();
+        let positions = layout.compute(&n, &edges);
+        let mut rows: Vec<f32> = n[1..]
+            .iter()
+            .map(|node| position_of(&positions, *node)[1])
+            .collect();
+        rows.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            rows,
+            vec![0.0, layout.node_spacing, 2.0 * layout.node_spacing]
+        );
+    }
+
+    #[test]
+    fn terminates_on_cycles() {
+        let n = ids(5);
+        // 0 -> 1 -> 2 -> 0 is broken at node 0, 2 -> 3 continues after the cycle,
+        // the self loop of 4 is ignored
+        let edges = [
+            (n[0], n[1]),
+            (n[1], n[2]),
+            (n[2], n[0]),
+            (n[2], n[3]),
+            (n[4], n[4]),
+        ];
+        assert_eq!(columns(&n, &edges), vec![0, 1, 2, 3, 0]);
+    }
+}
@@ -5,8 +5,13 @@ Bindings for [imnodes](https://github.com/Nelarius/imnodes) using [cimnodes](htt
 */
 
 use imnodes_sys as sys;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// export all low level functions
+///
+/// together with [EditorContext::raw] and [EditorContext::raw_style] this allows calling
+/// imnodes functions which are not wrapped yet
 #[cfg(feature = "include_low_level_bindings")]
 pub mod internal {
     pub use imnodes_sys::*;
@@ -24,10 +29,67 @@ pub use styling::*;
 mod scopes;
 pub use scopes::*;
 
+mod tokens;
+pub use tokens::*;
+
+mod persistence;
+pub use persistence::*;
+
+mod io;
+pub use io::*;
+
+mod events;
+pub use events::*;
+
+mod context_menu;
+pub use context_menu::*;
+
+mod drag_drop;
+pub use drag_drop::*;
+
+mod canvas;
+pub use canvas::*;
+
+mod badges;
+pub use badges::*;
+
+pub mod graph;
+
+pub mod undo;
+
+pub mod clipboard;
+
+pub mod layout;
+
+pub mod groups;
+
+pub mod typed;
+
+pub mod exec;
+
+pub mod registry;
+
+pub mod eval;
+
+pub mod id_map;
+
+pub mod dot;
+
+#[cfg(feature = "json")]
+pub mod document;
+
+#[cfg(feature = "graphml")]
+pub mod graphml;
+
+#[cfg(feature = "petgraph")]
+pub mod petgraph_interop;
+
 // maybe wrap those (same decision as in implot-rs)
 pub use sys::{ImVec2, Style};
 
 /// used to generate unique identifers for elements
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IdentifierGenerator {
     current_node: i32,
     current_pin: i32,
@@ -46,6 +108,18 @@ impl IdentifierGenerator {
         }
     }
 
+    /// generator whose first ids of every kind are `first`,
+    /// e.g. one past the highest id of a graph which was loaded without its generator
+    ///
+    /// with the `serde` feature the generator itself can be stored next to the graph instead
+    pub fn starting_at(first: i32) -> Self {
+        Self {
+            current_node: first,
+            current_pin: first,
+            current_link: first,
+        }
+    }
+
     /// Id for a Node
     pub fn next_node(&mut self) -> NodeId {
         let id = self.current_node;
@@ -85,7 +159,8 @@ impl IdentifierGenerator {
 /// Identifier for Attributes in nodes
 /// TODO document what precise uniqueness constraints do these have
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AttributeId {
     id: i32,
 }
@@ -96,21 +171,23 @@ impl Into<i32> for AttributeId {
     }
 }
 
-/// TODO look this up
+/// the spaces in which imnodes positions can be given,
+/// see [EditorContext::convert] to go from one to another
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub enum CoordinateSystem {
-    /// TODO
+    /// pixels on the screen, like the mouse position
     /// probably what you want
     ScreenSpace,
-    /// TODO
+    /// relative to the top left corner of the editor canvas
     EditorSpace,
-    /// TODO
+    /// editor space shifted by the panning, stays the same when the editor is panned
     GridSpace,
 }
 
 /// Identifier for a Node
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NodeId {
     id: i32,
 }
@@ -180,7 +257,8 @@ impl Into<i32> for NodeId {
 
 /// either input or output pin
 /// like attribute_id in the original source
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PinId {
     id: i32,
 }
@@ -198,7 +276,8 @@ impl PinId {
 }
 
 /// Id for an input
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct InputPinId {
     id: i32,
 }
@@ -216,7 +295,8 @@ impl Into<PinId> for InputPinId {
 }
 
 /// Id for an output
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OutputPinId {
     id: i32,
 }
@@ -235,7 +315,8 @@ impl Into<PinId> for OutputPinId {
 
 /// Id for a link
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LinkId {
     id: i32,
 }
@@ -244,7 +325,7 @@ impl LinkId {
     /// IsLinkDestroyed
     /// checks if the link of this LinkId got removed
     pub fn is_removed(&self, scope: &ScopeNone) -> bool {
-        Some(*self) == scope.get_dropped_link()
+        Some(*self) == scope.link_destroyed()
     }
 }
 
@@ -263,6 +344,27 @@ pub trait Hoverable {
     ///
     /// there is also [is_editor_hovered()] which does not depend on the scope
     fn is_hovered(self, _: &ScopeNone) -> bool;
+
+    /// show an imgui tooltip while the element is hovered
+    ///
+    /// takes the [ScopeNone] because the hover state is only known after EndNodeEditor
+    fn tooltip_on_hover<F: FnOnce()>(self, ui: &imgui::Ui, scope: &ScopeNone, f: F) -> bool
+    where
+        Self: Sized,
+    {
+        let hovered = self.is_hovered(scope);
+        if hovered {
+            ui.tooltip(f);
+        }
+        hovered
+    }
+}
+
+impl Hoverable for PinId {
+    /// isPinHovered
+    fn is_hovered(self, scope: &ScopeNone) -> bool {
+        Some(self) == scope.get_hovered_pin()
+    }
 }
 
 impl Hoverable for OutputPinId {
@@ -281,8 +383,8 @@ impl Hoverable for InputPinId {
 
 impl Hoverable for NodeId {
     /// isNodeHovered
-    fn is_hovered(self, _: &ScopeNone) -> bool {
-        Some(self) == get_hovered_node()
+    fn is_hovered(self, scope: &ScopeNone) -> bool {
+        Some(self) == scope.get_hovered_node()
     }
 }
 
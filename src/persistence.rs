@@ -0,0 +1,86 @@
+use crate::{sys, EditorContext};
+use std::{
+    ffi::CString,
+    io::{Error, ErrorKind, Result},
+    path::Path,
+};
+
+/// the bytes of the path as imnodes passes them to `fopen`
+#[cfg(unix)]
+fn to_c_path(path: &Path) -> Result<CString> {
+    use std::os::unix::ffi::OsStrExt;
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, error))
+}
+
+/// without the raw bytes of unix only paths which are valid unicode can be passed on
+#[cfg(not(unix))]
+fn to_c_path(path: &Path) -> Result<CString> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "path is not valid unicode"))?;
+    CString::new(path).map_err(|error| Error::new(ErrorKind::InvalidInput, error))
+}
+
+impl EditorContext {
+    /// SaveEditorStateToIniString
+    /// node positions and panning of this editor in the ini format of imnodes
+    pub fn save_state_to_ini_string(&self) -> String {
+        let mut size: sys::size_t = 0;
+        let data = unsafe { sys::imnodes_SaveEditorStateToIniString(self.raw, &mut size as _) };
+        if data.is_null() {
+            return String::new();
+        }
+        // the buffer is owned by imnodes and reused on the next call, so copy it out
+        let bytes = unsafe { std::slice::from_raw_parts(data as *const u8, size as usize) };
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+
+    /// LoadEditorStateFromIniString
+    /// restore a state created with [EditorContext::save_state_to_ini_string]
+    pub fn load_state_from_ini_string(&self, data: &str) {
+        unsafe {
+            sys::imnodes_LoadEditorStateFromIniString(
+                self.raw,
+                data.as_ptr() as _,
+                data.len() as sys::size_t,
+            )
+        };
+    }
+
+    /// SaveEditorStateToIniFile
+    ///
+    /// fails with [ErrorKind::InvalidInput] if the path contains a nul byte,
+    /// imnodes does not report whether the file could be written
+    pub fn save_state_to_ini_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = to_c_path(path.as_ref())?;
+        unsafe { sys::imnodes_SaveEditorStateToIniFile(self.raw, path.as_ptr()) };
+        Ok(())
+    }
+
+    /// LoadEditorStateFromIniFile
+    ///
+    /// fails with [ErrorKind::InvalidInput] if the path contains a nul byte,
+    /// imnodes does not report whether the file could be read
+    pub fn load_state_from_ini_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = to_c_path(path.as_ref())?;
+        unsafe { sys::imnodes_LoadEditorStateFromIniFile(self.raw, path.as_ptr()) };
+        Ok(())
+    }
+}
+
+/// SaveCurrentEditorStateToIniFile
+/// same as [EditorContext::save_state_to_ini_file] for the editor which is currently set
+pub fn save_current_editor_state_to_ini_file<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = to_c_path(path.as_ref())?;
+    unsafe { sys::imnodes_SaveCurrentEditorStateToIniFile(path.as_ptr()) };
+    Ok(())
+}
+
+/// LoadCurrentEditorStateFromIniFile
+/// same as [EditorContext::load_state_from_ini_file] for the editor which is currently set
+pub fn load_current_editor_state_from_ini_file<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = to_c_path(path.as_ref())?;
+    unsafe { sys::imnodes_LoadCurrentEditorStateFromIniFile(path.as_ptr()) };
+    Ok(())
+}
@@ -0,0 +1,74 @@
+/*!
+Conversion between [Graph] and a [StableGraph] of [petgraph], to run its algorithms
+(toposort, strongly connected components, dominators, ...) on the graph which is edited.
+
+Every node becomes a petgraph node and every link an edge from the node owning the output pin
+to the node owning the input pin. The node index `i` belongs to the `i`-th node of
+[Graph::nodes].
+
+```ignore
+let petgraph = graph.to_petgraph();
+let order = petgraph::algo::toposort(&petgraph, None)
+    .map(|order| order.into_iter().map(|index| petgraph[index].id).collect::<Vec<_>>());
+```
+*/
+
+use crate::{
+    graph::{Graph, Link, Node},
+    PinId,
+};
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use std::collections::HashMap;
+
+impl<N> Graph<N> {
+    /// petgraph which borrows the nodes as weights, the edge weights are the links
+    ///
+    /// links to pins which do not belong to a node of this graph are left out
+    pub fn to_petgraph(&self) -> StableGraph<&Node<N>, Link> {
+        let mut petgraph = StableGraph::with_capacity(self.nodes().len(), self.links().len());
+        let mut owners: HashMap<PinId, NodeIndex> = HashMap::new();
+        for node in self.nodes() {
+            let index = petgraph.add_node(node);
+            owners.extend(node.inputs.iter().map(|pin| (pin.id.into(), index)));
+            owners.extend(node.outputs.iter().map(|pin| (pin.id.into(), index)));
+        }
+        for link in self.links() {
+            let start = owners.get(&link.start.into());
+            let end = owners.get(&link.end.into());
+            if let (Some(start), Some(end)) = (start, end) {
+                petgraph.add_edge(*start, *end, *link);
+            }
+        }
+        petgraph
+    }
+
+    /// like [Graph::to_petgraph] but with copies of the nodes,
+    /// e.g. to edit them and convert back with [Graph::from_petgraph]
+    pub fn to_owned_petgraph(&self) -> StableGraph<Node<N>, Link>
+    where
+        N: Clone,
+    {
+        self.to_petgraph()
+            .map(|_, node| (*node).clone(), |_, link| *link)
+    }
+
+    /// graph with the nodes and the links (the edge weights) of a petgraph
+    ///
+    /// the pins of a link are not checked against the nodes of its edge.
+    /// New ids continue after the highest id in the petgraph.
+    pub fn from_petgraph(mut petgraph: StableGraph<Node<N>, Link>) -> Self {
+        let mut graph = Self::new();
+        let links: Vec<Link> = petgraph.edge_weights().copied().collect();
+        let indices: Vec<NodeIndex> = petgraph.node_indices().collect();
+        for index in indices {
+            if let Some(node) = petgraph.remove_node(index) {
+                graph.insert_node(node);
+            }
+        }
+        for link in links {
+            graph.insert_link(link);
+        }
+        graph.skip_used_ids();
+        graph
+    }
+}
@@ -0,0 +1,438 @@
+/*!
+Node definitions which can be instantiated into a [Graph].
+
+Every [NodeTemplate] describes the title, the pins, how the user data of a new node is created
+and optionally the widgets drawn inside the node.
+[NodeRegistry::add_node_menu] lists all templates grouped by category and
+[NodeRegistry::instantiate] adds a node with fresh ids to a graph.
+[NodePalette] is a popup to search the templates by typing.
+
+```ignore
+let mut registry = NodeRegistry::new();
+let add = registry.register(
+    NodeTemplate::new("Add", || Op::Add)
+        .category("Math")
+        .input("a", PinShape::Circle)
+        .input("b", PinShape::Circle)
+        .output("sum", PinShape::CircleFilled),
+);
+
+ui.popup(im_str!("add node"), || {
+    if let Some(template) = registry.add_node_menu(&ui) {
+        registry.instantiate(template, &mut graph);
+    }
+});
+```
+*/
+
+use crate::{graph::Graph, CoordinateSystem, EditorContext, NodeId, PinShape, ScopeNode};
+use imgui::{im_str, FocusedWidget, ImString, Key, MenuItem, Selectable, Ui};
+use std::collections::HashMap;
+
+/// label and shape of a pin of a [NodeTemplate]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinSpec {
+    /// text next to the pin
+    pub label: String,
+    /// how the pin is drawn
+    pub shape: PinShape,
+}
+
+/// index of a [NodeTemplate] in a [NodeRegistry]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct TemplateId {
+    index: usize,
+}
+
+/// draws the widgets of a node, see [NodeTemplate::widgets]
+type Widgets<N> = Box<dyn Fn(&Ui, &mut ScopeNode<'_>, &mut N)>;
+
+/// definition of a kind of node
+pub struct NodeTemplate<N> {
+    /// text in the title bar of the created nodes and in the menu
+    pub title: String,
+    /// submenu of [NodeRegistry::add_node_menu], empty for the top level
+    pub category: String,
+    /// input pins, top to bottom
+    pub inputs: Vec<PinSpec>,
+    /// output pins, top to bottom
+    pub outputs: Vec<PinSpec>,
+    data: Box<dyn Fn() -> N>,
+    widgets: Option<Widgets<N>>,
+}
+
+impl<N> std::fmt::Debug for NodeTemplate<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeTemplate")
+            .field("title", &self.title)
+            .field("category", &self.category)
+            .field("inputs", &self.inputs)
+            .field("outputs", &self.outputs)
+            .field("widgets", &self.widgets.is_some())
+            .finish()
+    }
+}
+
+impl<N> NodeTemplate<N> {
+    /// template without pins, `data` creates the user data of every new node
+    pub fn new<S: Into<String>, D: Fn() -> N + 'static>(title: S, data: D) -> Self {
+        Self {
+            title: title.into(),
+            category: String::new(),
+            inputs: vec![],
+            outputs: vec![],
+            data: Box::new(data),
+            widgets: None,
+        }
+    }
+
+    /// group the template in a submenu
+    pub fn category<S: Into<String>>(mut self, category: S) -> Self {
+        self.category = category.into();
+        self
+    }
+
+    /// add an input pin
+    pub fn input<S: Into<String>>(mut self, label: S, shape: PinShape) -> Self {
+        self.inputs.push(PinSpec {
+            label: label.into(),
+            shape,
+        });
+        self
+    }
+
+    /// add an output pin
+    pub fn output<S: Into<String>>(mut self, label: S, shape: PinShape) -> Self {
+        self.outputs.push(PinSpec {
+            label: label.into(),
+            shape,
+        });
+        self
+    }
+
+    /// widgets drawn inside every node of this template, see [NodeRegistry::draw_widgets]
+    pub fn widgets<W: Fn(&Ui, &mut ScopeNode<'_>, &mut N) + 'static>(mut self, widgets: W) -> Self {
+        self.widgets = Some(Box::new(widgets));
+        self
+    }
+}
+
+/// all node templates of an application and which node was created from which template
+#[derive(Debug)]
+pub struct NodeRegistry<N> {
+    templates: Vec<NodeTemplate<N>>,
+    kinds: HashMap<NodeId, TemplateId>,
+}
+
+impl<N> Default for NodeRegistry<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N> NodeRegistry<N> {
+    /// no templates
+    pub fn new() -> Self {
+        Self {
+            templates: vec![],
+            kinds: HashMap::new(),
+        }
+    }
+
+    /// add a template, templates are listed in the order they were registered
+    pub fn register(&mut self, template: NodeTemplate<N>) -> TemplateId {
+        self.templates.push(template);
+        TemplateId {
+            index: self.templates.len() - 1,
+        }
+    }
+
+    /// all templates with their ids
+    pub fn templates(&self) -> impl Iterator<Item = (TemplateId, &NodeTemplate<N>)> {
+        self.templates
+            .iter()
+            .enumerate()
+            .map(|(index, template)| (TemplateId { index }, template))
+    }
+
+    /// get a template by id
+    ///
+    /// panics if the id is from another registry
+    pub fn template(&self, id: TemplateId) -> &NodeTemplate<N> {
+        &self.templates[id.index]
+    }
+
+    /// template a node was created from with [NodeRegistry::instantiate]
+    pub fn kind_of(&self, node: NodeId) -> Option<TemplateId> {
+        self.kinds.get(&node).copied()
+    }
+
+    /// add a node with the pins and default data of the template to the graph
+    pub fn instantiate(&mut self, id: TemplateId, graph: &mut Graph<N>) -> NodeId {
+        let template = &self.templates[id.index];
+        let node = graph.add_node(template.title.clone(), (template.data)());
+        for pin in &template.inputs {
+            graph.add_input(node, pin.label.clone(), pin.shape);
+        }
+        for pin in &template.outputs {
+            graph.add_output(node, pin.label.clone(), pin.shape);
+        }
+        self.kinds.insert(node, id);
+        node
+    }
+
+    /// forget the template of a node, e.g. after it was removed from the graph
+    pub fn forget(&mut self, node: NodeId) -> Option<TemplateId> {
+        self.kinds.remove(&node)
+    }
+
+    /// draw the widgets of the template the node was created from,
+    /// meant to be called from the body of [Graph::render]
+    pub fn draw_widgets(
+        &self,
+        ui: &Ui,
+        scope: &mut ScopeNode<'_>,
+        node: &mut crate::graph::Node<N>,
+    ) {
+        let widgets = self
+            .kind_of(node.id)
+            .and_then(|kind| self.templates[kind.index].widgets.as_ref());
+        if let Some(widgets) = widgets {
+            widgets(ui, scope, &mut node.data);
+        }
+    }
+
+    /// menu items for all templates with a submenu per category,
+    /// returns the template the user clicked on
+    ///
+    /// has to be called inside a popup or menu
+    pub fn add_node_menu(&self, ui: &Ui) -> Option<TemplateId> {
+        let mut chosen = None;
+        let mut categories: Vec<&str> = vec![];
+        for template in &self.templates {
+            if !categories.contains(&template.category.as_str()) {
+                categories.push(&template.category);
+            }
+        }
+
+        for category in categories {
+            let mut items = || {
+                for (id, template) in self.templates() {
+                    if template.category == category
+                        && MenuItem::new(&ImString::new(template.title.as_str())).build(ui)
+                    {
+                        chosen = Some(id);
+                    }
+                }
+            };
+            if category.is_empty() {
+                items();
+            } else {
+                ui.menu(&ImString::new(category), true, items);
+            }
+        }
+        chosen
+    }
+}
+
+/// score of `text` for a search, `None` if the characters of `query` do not appear in `text`
+/// in the same order, higher is better
+///
+/// matches at the start of a word and consecutive matches count more
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut text_chars = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .enumerate()
+        .peekable();
+    // character before the one which is compared and the index of the last match
+    let mut previous: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        if wanted.is_whitespace() {
+            continue;
+        }
+        loop {
+            let (index, c) = text_chars.next()?;
+            let word_start = previous.is_none_or(|p| !p.is_alphanumeric());
+            previous = Some(c);
+            if c == wanted {
+                let consecutive = last_match.is_some_and(|last| last + 1 == index);
+                last_match = Some(index);
+                score += 1 + 3 * i32::from(word_start) + 2 * i32::from(consecutive);
+                break;
+            }
+        }
+    }
+    // prefer shorter titles if the query matches equally well
+    Some(score * 100 - text.len() as i32)
+}
+
+/// popup to search the templates of a [NodeRegistry] by typing,
+/// Enter or a click spawns the best match at the position the palette was opened for
+///
+/// ```ignore
+/// if ui.is_key_pressed(ui.key_index(Key::Space)) {
+///     palette.open(&ui, menu.position());
+/// }
+/// palette.draw(&ui, &mut registry, &mut graph, &context);
+/// ```
+#[derive(Debug)]
+pub struct NodePalette {
+    query: ImString,
+    position: [f32; 2],
+    selected: usize,
+}
+
+impl Default for NodePalette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodePalette {
+    /// closed palette
+    pub fn new() -> Self {
+        Self {
+            query: ImString::with_capacity(64),
+            position: [0.0, 0.0],
+            selected: 0,
+        }
+    }
+
+    /// open the popup with an empty query, new nodes are placed at the grid space `position`
+    pub fn open(&mut self, ui: &Ui, position: [f32; 2]) {
+        self.query.clear();
+        self.position = position;
+        self.selected = 0;
+        ui.open_popup(im_str!("imnodes_node_palette"));
+    }
+
+    /// grid space position new nodes are placed at
+    pub fn position(&self) -> [f32; 2] {
+        self.position
+    }
+
+    /// templates matching the current query, best match first
+    pub fn matches<'r, N>(
+        &self,
+        registry: &'r NodeRegistry<N>,
+    ) -> Vec<(TemplateId, &'r NodeTemplate<N>)> {
+        let query = self.query.to_str();
+        let mut matches: Vec<_> = registry
+            .templates()
+            .filter_map(|(id, template)| {
+                let text = if template.category.is_empty() {
+                    template.title.clone()
+                } else {
+                    format!("{} {}", template.category, template.title)
+                };
+                fuzzy_score(query, &text).map(|score| (score, id, template))
+            })
+            .collect();
+        // stable, so equal scores keep the registration order
+        matches.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+        matches
+            .into_iter()
+            .map(|(_, id, template)| (id, template))
+            .collect()
+    }
+
+    /// draw the popup if it is open, returns the node which was spawned
+    ///
+    /// arrow keys change the highlighted entry, Escape closes the palette
+    pub fn draw<N>(
+        &mut self,
+        ui: &Ui,
+        registry: &mut NodeRegistry<N>,
+        graph: &mut Graph<N>,
+        context: &EditorContext,
+    ) -> Option<NodeId> {
+        let mut chosen = None;
+        ui.popup(im_str!("imnodes_node_palette"), || {
+            if ui.is_window_appearing() {
+                ui.set_keyboard_focus_here(FocusedWidget::Next);
+            }
+            let entered = ui
+                .input_text(im_str!("##search"), &mut self.query)
+                .resize_buffer(true)
+                .enter_returns_true(true)
+                .build();
+
+            let matches = self.matches(registry);
+            if ui.is_key_pressed(ui.key_index(Key::DownArrow)) {
+                self.selected += 1;
+            }
+            if ui.is_key_pressed(ui.key_index(Key::UpArrow)) {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+            for (index, (id, template)) in matches.iter().enumerate() {
+                let label = ImString::new(template.title.as_str());
+                if Selectable::new(&label)
+                    .selected(index == self.selected)
+                    .build(ui)
+                {
+                    chosen = Some(*id);
+                }
+            }
+            if entered {
+                chosen = matches.get(self.selected).map(|(id, _)| *id);
+            }
+
+            if chosen.is_some() || ui.is_key_pressed(ui.key_index(Key::Escape)) {
+                ui.close_current_popup();
+            }
+        });
+
+        let template = chosen?;
+        let node = registry.instantiate(template, graph);
+        let _previous = context.make_current();
+        let [x, y] = self.position;
+        node.set_position(x, y, CoordinateSystem::GridSpace);
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_starts_count_more() {
+        // the longer title wins because `m` starts a word in it
+        assert!(fuzzy_score("m", "Multiply") > fuzzy_score("m", "Sum"));
+        assert!(fuzzy_score("g", "Add Gain") > fuzzy_score("g", "Bang"));
+    }
+
+    #[test]
+    fn consecutive_matches_count_more() {
+        // same length, neither match starts a word
+        assert!(fuzzy_score("lt", "Salt") > fuzzy_score("lt", "Slot"));
+    }
+
+    #[test]
+    fn shorter_title_wins_a_tie() {
+        assert!(fuzzy_score("add", "Add") > fuzzy_score("add", "Add Vector"));
+    }
+
+    #[test]
+    fn characters_must_appear_in_order() {
+        assert_eq!(fuzzy_score("xyz", "Add"), None);
+        assert_eq!(fuzzy_score("da", "Add"), None);
+        assert_eq!(fuzzy_score("addd", "Add"), None);
+        assert!(fuzzy_score("", "Add").is_some());
+    }
+
+    #[test]
+    fn ignores_case_and_spaces_in_the_query() {
+        assert_eq!(fuzzy_score("ADD", "add"), fuzzy_score("add", "ADD"));
+        assert_eq!(
+            fuzzy_score("mul", "Multiply"),
+            fuzzy_score("MuL", "multiply")
+        );
+        assert_eq!(fuzzy_score("a d", "Add"), fuzzy_score("ad", "Add"));
+    }
+}
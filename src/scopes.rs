@@ -6,30 +6,97 @@ As soon as you enter a nested scope you are not allowed to call methods from the
 This is why every method which takes a closure and calls it with a new scope takes `&mut self`.
 */
 
+use imgui::ImColor;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
+    marker::PhantomData,
+    rc::Rc,
+};
+
 use crate::{
-    sys, AttributeId, EditorContext, Hoverable, InputPinId, Link, LinkId, NodeId, OutputPinId,
-    PinId, PinShape,
+    canvas::draw_background, editor_begin, io::update_modifiers, styling::push_disabled_colors,
+    sys, AttributeId, AttributeToken, EditorContext, EditorContextGuard, Hoverable, InputPinId,
+    Link, LinkId, LinkStyle, NodeColors, NodeId, NodeToken, OutputPinId, PinId, PinShape,
+    TitleBarToken,
 };
 
+/// makes a scope invariant over `'s`, so the closures which get a scope have to work for any
+/// lifetime and the borrow checker rejects storing or returning the scope
+type Invariant<'s> = PhantomData<&'s mut &'s ()>;
+
 /// entry point
 ///
 /// BeginNodeEditor
 /// ...
 /// EndNodeEditor
-pub fn editor<F: FnOnce(ScopeEditor)>(context: &mut EditorContext, f: F) -> ScopeNone {
-    context.set_as_current_editor();
+///
+/// `ui` is taken as proof that an imgui frame is active, without one imnodes crashes,
+/// and for the state of the [ModifierKey](crate::ModifierKey)s.
+/// `context` stays the current editor until the returned [ScopeNone] is dropped,
+/// afterwards the previously current editor is restored.
+/// The value returned by `f` is passed through.
+pub fn editor<'a, F, R>(ui: &imgui::Ui, context: &'a mut EditorContext, f: F) -> (ScopeNone<'a>, R)
+where
+    F: FnOnce(ScopeEditor<'_>) -> R,
+{
+    // the tokens call End* even if `f` panics, so imnodes is in a sane state for the next frame
+    let token = editor_begin(ui, context);
+    let result = f(token.new_scope());
+    (token.end(), result)
+}
+
+/// [editor] for a closure which can fail
+///
+/// EndNodeEditor is called before the error is returned and the previously current editor
+/// is restored, so `?` can be used inside `f` without leaving imnodes in a broken state.
+pub fn editor_try<'a, F, T, E>(
+    ui: &imgui::Ui,
+    context: &'a mut EditorContext,
+    f: F,
+) -> Result<(ScopeNone<'a>, T), E>
+where
+    F: FnOnce(ScopeEditor<'_>) -> Result<T, E>,
+{
+    let (scope, result) = editor(ui, context, f);
+    result.map(|value| (scope, value))
+}
 
+/// make the context current and call BeginNodeEditor
+pub(crate) fn begin_editor<'a>(
+    ui: &imgui::Ui,
+    context: &'a EditorContext,
+) -> EditorContextGuard<'a> {
+    let guard = EditorContextGuard::new(context);
+    update_modifiers(ui.io());
+
+    // the canvas is a child window which starts at the cursor and fills the remaining space
+    let mut origin = imgui::sys::ImVec2::zero();
+    let mut size = imgui::sys::ImVec2::zero();
+    unsafe {
+        imgui::sys::igGetCursorScreenPos(&mut origin);
+        imgui::sys::igGetContentRegionAvail(&mut size);
+    }
+    guard.context.canvas_origin.set([origin.x, origin.y]);
+    guard.context.canvas_size.set([size.x, size.y]);
+
+    // imnodes fills the canvas with the grid background in BeginNodeEditor,
+    // so a custom background is drawn into the parent window and the grid is made transparent
+    let background = draw_background(guard.context);
     unsafe { sys::imnodes_BeginNodeEditor() };
-    f(ScopeEditor {});
-    unsafe { sys::imnodes_EndNodeEditor() };
-    ScopeNone {}
+    if let Some(background) = background {
+        background.end(guard.context);
+    }
+    guard
 }
 
 /// Original Scopes turned into compile time checks:
 /// Scope_None = 1,
 #[derive(Debug)]
-pub struct ScopeNone {}
-impl ScopeNone {
+pub struct ScopeNone<'a> {
+    pub(crate) guard: EditorContextGuard<'a>,
+}
+impl ScopeNone<'_> {
     /// check the position of the mosue
     pub fn is_hovered(&self, id: impl Hoverable) -> bool {
         id.is_hovered(&self)
@@ -51,7 +118,7 @@ impl ScopeNone {
     /// selected_nodes builds on top of this
     pub fn num_selected_nodes(&self) -> u32 {
         let num = unsafe { sys::imnodes_NumSelectedNodes() };
-        assert!(num > 0);
+        assert!(num >= 0);
         num as u32
     }
 
@@ -59,26 +126,60 @@ impl ScopeNone {
     /// selected_links builds on top of this
     pub fn num_selected_links(&self) -> u32 {
         let num = unsafe { sys::imnodes_NumSelectedLinks() };
-        assert!(num > 0);
+        assert!(num >= 0);
         num as u32
     }
 
+    /// is at least one node selected
+    pub fn has_selected_nodes(&self) -> bool {
+        self.num_selected_nodes() > 0
+    }
+
+    /// is at least one link selected
+    pub fn has_selected_links(&self) -> bool {
+        self.num_selected_links() > 0
+    }
+
     /// GetSelectedNodes
+    /// empty if no node is selected
     pub fn selected_nodes(&self) -> Vec<NodeId> {
-        let nr_nodes = self.num_selected_nodes() as usize;
-        let mut nodes = vec![NodeId { id: 0 }; nr_nodes];
-        unsafe { sys::imnodes_GetSelectedNodes(nodes.as_mut_ptr() as _) };
+        let mut nodes = vec![];
+        self.selected_nodes_into(&mut nodes);
         nodes
     }
 
+    /// GetSelectedNodes
+    /// like [ScopeNone::selected_nodes] but reuses the allocation of `nodes`,
+    /// which is cleared first
+    pub fn selected_nodes_into(&self, nodes: &mut Vec<NodeId>) {
+        let nr_nodes = self.num_selected_nodes() as usize;
+        nodes.clear();
+        nodes.resize(nr_nodes, NodeId { id: 0 });
+        if nr_nodes > 0 {
+            unsafe { sys::imnodes_GetSelectedNodes(nodes.as_mut_ptr() as _) };
+        }
+    }
+
     /// GetSelectedLinks
+    /// empty if no link is selected
     pub fn selected_links(&self) -> Vec<LinkId> {
-        let nr_links = self.num_selected_links() as usize;
-        let mut links = vec![LinkId { id: 0 }; nr_links];
-        unsafe { sys::imnodes_GetSelectedLinks(links.as_mut_ptr() as _) };
+        let mut links = vec![];
+        self.selected_links_into(&mut links);
         links
     }
 
+    /// GetSelectedLinks
+    /// like [ScopeNone::selected_links] but reuses the allocation of `links`,
+    /// which is cleared first
+    pub fn selected_links_into(&self, links: &mut Vec<LinkId>) {
+        let nr_links = self.num_selected_links() as usize;
+        links.clear();
+        links.resize(nr_links, LinkId { id: 0 });
+        if nr_links > 0 {
+            unsafe { sys::imnodes_GetSelectedLinks(links.as_mut_ptr() as _) };
+        }
+    }
+
     /// IsLinkCreated
     pub fn links_created(&self) -> Option<Link> {
         let mut started_at_node_id: i32 = -1;
@@ -105,7 +206,15 @@ impl ScopeNone {
         //     )
         // };
 
-        if is_created {
+        let display_only = self.guard.context.display_only_pins.borrow();
+        if is_created
+            && !display_only.contains(&PinId {
+                id: started_at_attribute_id,
+            })
+            && !display_only.contains(&PinId {
+                id: ended_at_attribute_id,
+            })
+        {
             Some(Link {
                 start_node: NodeId {
                     id: started_at_node_id,
@@ -127,7 +236,11 @@ impl ScopeNone {
     }
 
     /// IsLinkDestroyed
-    pub fn get_dropped_link(&self) -> Option<LinkId> {
+    /// imnodes only reports links which the user detached by dragging them off a pin
+    /// (see [AttributeFlag::EnableLinkDetachWithDragClick](crate::AttributeFlag) and
+    /// [Context::set_link_detach_with_modifier_click](crate::Context::set_link_detach_with_modifier_click)),
+    /// links which the application stops submitting are never reported here
+    pub fn link_destroyed(&self) -> Option<LinkId> {
         let mut id: i32 = -1;
         if unsafe { sys::imnodes_IsLinkDestroyed(&mut id as _) } {
             Some(LinkId { id })
@@ -136,6 +249,17 @@ impl ScopeNone {
         }
     }
 
+    /// IsNodeHovered, nodes added with [ScopeEditor::add_disabled_node] are never hovered
+    pub fn get_hovered_node(&self) -> Option<NodeId> {
+        crate::get_hovered_node()
+            .filter(|id| !self.guard.context.disabled_nodes.borrow().contains(id))
+    }
+
+    /// same as [ScopeNone::link_destroyed]
+    pub fn get_dropped_link(&self) -> Option<LinkId> {
+        self.link_destroyed()
+    }
+
     /// IsPinHovered
     pub fn get_hovered_pin(&self) -> Option<PinId> {
         let mut id: i32 = -1;
@@ -173,7 +297,7 @@ impl ScopeNone {
     pub fn from_where_link_started(&self) -> Option<PinId> {
         let mut id: i32 = -1;
         let ok = unsafe { sys::imnodes_IsLinkStarted(&mut id as _) };
-        if ok {
+        if ok && !self.is_display_only(PinId { id }) {
             Some(PinId { id })
         } else {
             None
@@ -184,26 +308,133 @@ impl ScopeNone {
     pub fn from_where_link_dropped(&self, including_detached_links: bool) -> Option<PinId> {
         let mut id: i32 = -1;
         let ok = unsafe { sys::imnodes_IsLinkDropped(&mut id as _, including_detached_links) };
-        if ok {
+        if ok && !self.is_display_only(PinId { id }) {
             Some(PinId { id })
         } else {
             None
         }
     }
+
+    /// the pin was marked with [ScopeNode::set_display_only] in this frame
+    pub(crate) fn is_display_only(&self, pin: PinId) -> bool {
+        self.guard.context.display_only_pins.borrow().contains(&pin)
+    }
 }
 
 /// Scope_Editor = 1 << 1,
 #[derive(Debug)]
-pub struct ScopeEditor {}
-impl ScopeEditor {
+pub struct ScopeEditor<'s> {
+    _scope: Invariant<'s>,
+    /// screen space position of the top left corner of the canvas in this frame
+    pub(crate) canvas_origin: [f32; 2],
+    /// pending link at the end of the last frame
+    pub(crate) pending_link: Option<PinId>,
+    /// pushed around EndNodeEditor, shared by all scopes of one editor
+    pub(crate) pending_link_color: Rc<Cell<Option<ImColor>>>,
+    /// pins marked with [ScopeNode::set_display_only], shared by all scopes of one editor
+    pub(crate) display_only_pins: Rc<RefCell<HashSet<PinId>>>,
+    /// nodes added with [ScopeEditor::add_disabled_node], shared by all scopes of one editor
+    pub(crate) disabled_nodes: Rc<RefCell<HashSet<NodeId>>>,
+    /// nodes which were disabled in the last frame, they are made draggable again
+    /// when they are added with [ScopeEditor::add_node]
+    pub(crate) previously_disabled: Rc<HashSet<NodeId>>,
+}
+impl ScopeEditor<'_> {
+    pub(crate) fn new(context: &EditorContext) -> Self {
+        Self {
+            _scope: PhantomData,
+            canvas_origin: context.canvas_origin.get(),
+            pending_link: context
+                .pending_link
+                .get()
+                .filter(|pin| !context.display_only_pins.borrow().contains(pin)),
+            pending_link_color: Rc::default(),
+            display_only_pins: Rc::default(),
+            disabled_nodes: Rc::default(),
+            previously_disabled: Rc::new(context.disabled_nodes.borrow().clone()),
+        }
+    }
+
+    /// scope of the same editor
+    pub(crate) fn share<'s>(&self) -> ScopeEditor<'s> {
+        ScopeEditor {
+            _scope: PhantomData,
+            canvas_origin: self.canvas_origin,
+            pending_link: self.pending_link,
+            pending_link_color: self.pending_link_color.clone(),
+            display_only_pins: self.display_only_pins.clone(),
+            disabled_nodes: self.disabled_nodes.clone(),
+            previously_disabled: self.previously_disabled.clone(),
+        }
+    }
+
+    /// color of the link which the user is dragging in this frame, e.g. green over a compatible
+    /// pin and red over an incompatible one, see [ScopeEditor::pending_link]
+    ///
+    /// there is no thickness because imnodes draws all links with the same
+    /// [StyleVar::LinkThickness](crate::StyleVar)
+    pub fn set_pending_link_color<C: Into<ImColor>>(&self, color: C) {
+        self.pending_link_color.set(Some(color.into()));
+    }
+
     /// BeginNode
     /// ...
     /// EndNode
-    pub fn add_node<F: FnOnce(ScopeNode)>(&mut self, id: NodeId, f: F) {
-        unsafe { sys::imnodes_BeginNode(id.into()) }
+    pub fn add_node<F: FnOnce(ScopeNode<'_>) -> R, R>(&mut self, id: NodeId, f: F) -> R {
+        if self.previously_disabled.contains(&id) {
+            id.set_draggable(true);
+        }
+        let _token = NodeToken::begin(id, self.display_only_pins.clone());
+        f(ScopeNode::new(self.display_only_pins.clone()))
+    }
 
-        f(ScopeNode {});
-        unsafe { sys::imnodes_EndNode() };
+    /// [ScopeEditor::add_node] for a closure which can fail, EndNode is called before
+    /// the error is returned
+    pub fn add_node_try<F, T, E>(&mut self, id: NodeId, f: F) -> Result<T, E>
+    where
+        F: FnOnce(ScopeNode<'_>) -> Result<T, E>,
+    {
+        self.add_node(id, f)
+    }
+
+    /// [ScopeEditor::add_node] with its own title bar and background colors,
+    /// the colors are pushed before BeginNode and popped after EndNode
+    pub fn node_colored<F: FnOnce(ScopeNode<'_>) -> R, R>(
+        &mut self,
+        id: NodeId,
+        colors: NodeColors,
+        f: F,
+    ) -> R {
+        let tokens = colors.push();
+        let result = self.add_node(id, f);
+        for token in tokens.into_iter().rev() {
+            token.pop();
+        }
+        result
+    }
+
+    /// [ScopeEditor::add_node] grayed out, e.g. for a bypassed node in an audio graph
+    ///
+    /// the node can not be dragged, no links can be created with its pins and it is left out of
+    /// [ScopeNone::get_hovered_node]. The widgets inside are drawn half transparent but still
+    /// react to the mouse, this version of imgui has no disabled state.
+    /// The node can be dragged again once it is added with [ScopeEditor::add_node]
+    pub fn add_disabled_node<F: FnOnce(ScopeNode<'_>) -> R, R>(&mut self, id: NodeId, f: F) -> R {
+        self.disabled_nodes.borrow_mut().insert(id);
+        id.set_draggable(false);
+
+        let tokens = push_disabled_colors();
+        let result = {
+            let _token = NodeToken::begin(id, self.display_only_pins.clone());
+            let mut scope = ScopeNode::new(self.display_only_pins.clone());
+            scope.disabled = true;
+            let _alpha = AlphaToken::push(0.5);
+            f(scope)
+        };
+        for token in tokens.into_iter().rev() {
+            token.pop();
+        }
+        result
     }
 
     /// Link
@@ -211,6 +442,37 @@ impl ScopeEditor {
         unsafe { sys::imnodes_Link(id.into(), input.into(), output.into()) }
     }
 
+    /// [ScopeEditor::add_link] for every link in the slice
+    pub fn add_links(&self, links: &[(LinkId, InputPinId, OutputPinId)]) {
+        self.add_links_iter(links.iter().copied());
+    }
+
+    /// [ScopeEditor::add_link] for every link of the iterator, e.g. to map the links of an
+    /// application graph without collecting them first
+    pub fn add_links_iter<I>(&self, links: I)
+    where
+        I: IntoIterator<Item = (LinkId, InputPinId, OutputPinId)>,
+    {
+        for (id, input, output) in links {
+            unsafe { sys::imnodes_Link(id.into(), input.into(), output.into()) }
+        }
+    }
+
+    /// [ScopeEditor::add_link] with its own colors, e.g. to draw invalid links red
+    pub fn add_link_styled(
+        &self,
+        id: LinkId,
+        input: InputPinId,
+        output: OutputPinId,
+        style: LinkStyle,
+    ) {
+        let tokens = style.push();
+        self.add_link(id, input, output);
+        for token in tokens.into_iter().rev() {
+            token.pop();
+        }
+    }
+
     /// IsAnyAttributeActive
     pub fn get_active_attribute(&self) -> Option<AttributeId> {
         let mut id: i32 = -1;
@@ -230,41 +492,251 @@ impl ScopeEditor {
 
 /// Scope_Node = 1 << 2,
 #[derive(Debug)]
-pub struct ScopeNode {}
-impl ScopeNode {
+pub struct ScopeNode<'s> {
+    _scope: Invariant<'s>,
+    /// see [ScopeEditor]
+    display_only_pins: Rc<RefCell<HashSet<PinId>>>,
+    /// all pins are display only, see [ScopeEditor::add_disabled_node]
+    disabled: bool,
+}
+impl ScopeNode<'_> {
+    pub(crate) fn new(display_only_pins: Rc<RefCell<HashSet<PinId>>>) -> Self {
+        Self {
+            _scope: PhantomData,
+            display_only_pins,
+            disabled: false,
+        }
+    }
+
+    /// links can not be created from or to this pin in this frame, existing links are still drawn
+    ///
+    /// imnodes has no such flag, so the user can still drag a link out of the pin
+    /// but it is drawn transparent from the next frame on, and the pin is left out of
+    /// [ScopeNone::links_created], [ScopeNone::from_where_link_started],
+    /// [ScopeNone::from_where_link_dropped] and [ScopeNone::pending_link]
+    pub fn set_display_only<P: Into<PinId>>(&self, pin: P) {
+        self.display_only_pins.borrow_mut().insert(pin.into());
+    }
+
+    /// [ScopeNode::add_input] for a pin marked with [ScopeNode::set_display_only]
+    pub fn add_display_input<F: FnOnce() -> R, R>(
+        &mut self,
+        id: InputPinId,
+        shape: PinShape,
+        f: F,
+    ) -> R {
+        self.set_display_only(id);
+        self.add_input(id, shape, f)
+    }
+
+    /// [ScopeNode::add_output] for a pin marked with [ScopeNode::set_display_only]
+    pub fn add_display_output<F: FnOnce() -> R, R>(
+        &mut self,
+        id: OutputPinId,
+        shape: PinShape,
+        f: F,
+    ) -> R {
+        self.set_display_only(id);
+        self.add_output(id, shape, f)
+    }
+
     /// BeginNodeTitleBar
     /// ....
     /// EndNodeTitleBar
-    pub fn add_titlebar<F: FnOnce()>(&mut self, f: F) {
-        unsafe { sys::imnodes_BeginNodeTitleBar() }
-        f();
-        unsafe { sys::imnodes_EndNodeTitleBar() }
+    pub fn add_titlebar<F: FnOnce() -> R, R>(&mut self, f: F) -> R {
+        let _token = TitleBarToken::begin();
+        f()
+    }
+
+    /// [ScopeNode::add_titlebar] with a progress fill behind the title, e.g. for a long running
+    /// computation, `fraction` is clamped to 0..=1
+    ///
+    /// the fill has the color of imgui progress bars, its full width is the width of the node
+    /// in the last frame, so nothing is drawn in the first frame of a node
+    pub fn add_titlebar_with_progress<F: FnOnce() -> R, R>(&mut self, fraction: f32, f: F) -> R {
+        // draw the title into a second channel so the fill can be put behind it afterwards,
+        // imnodes already splits the draw list into one channel per node
+        let split = ChannelSplit::new(2);
+        split.set_channel(1);
+
+        let result = self.add_titlebar(f);
+
+        // EndNodeTitleBar adds the padded title bar as the last item
+        let mut min = imgui::sys::ImVec2::zero();
+        let mut max = imgui::sys::ImVec2::zero();
+        unsafe {
+            imgui::sys::igGetItemRectMin(&mut min);
+            imgui::sys::igGetItemRectMax(&mut max);
+        }
+        let fraction = fraction.clamp(0.0, 1.0);
+        let corners = if fraction < 1.0 {
+            imgui::sys::ImDrawCornerFlags_TopLeft
+        } else {
+            imgui::sys::ImDrawCornerFlags_Top
+        };
+        split.set_channel(0);
+        if fraction > 0.0 && max.x > min.x {
+            unsafe {
+                imgui::sys::ImDrawList_AddRectFilled(
+                    split.draw_list,
+                    min,
+                    imgui::sys::ImVec2 {
+                        x: min.x + fraction * (max.x - min.x),
+                        y: max.y,
+                    },
+                    imgui::sys::igGetColorU32Col(imgui::sys::ImGuiCol_PlotHistogram as _, 1.0),
+                    (*sys::imnodes_GetStyle()).node_corner_rounding,
+                    corners as _,
+                );
+            }
+        }
+        result
+    }
+
+    /// [ScopeNode::add_titlebar] for a closure which can fail, EndNodeTitleBar is called before
+    /// the error is returned
+    pub fn add_titlebar_try<F: FnOnce() -> Result<T, E>, T, E>(&mut self, f: F) -> Result<T, E> {
+        self.add_titlebar(f)
     }
 
     /// BeginInputAttribute
     /// ...
     /// EndInputAttribute
-    pub fn add_input<F: FnOnce()>(&mut self, id: InputPinId, shape: PinShape, f: F) {
-        unsafe { sys::imnodes_BeginInputAttribute(id.into(), shape as u32) };
-        f();
-        unsafe { sys::imnodes_EndInputAttribute() };
+    pub fn add_input<F: FnOnce() -> R, R>(&mut self, id: InputPinId, shape: PinShape, f: F) -> R {
+        if self.disabled {
+            self.set_display_only(id);
+        }
+        let _token = AttributeToken::begin_input(id, shape);
+        f()
     }
 
     /// BeginOutputAttribute
     /// ...
     /// EndOutputAttribute
-    pub fn add_output<F: FnOnce()>(&mut self, id: OutputPinId, shape: PinShape, f: F) {
-        unsafe { sys::imnodes_BeginOutputAttribute(id.into(), shape as u32) };
-        f();
-        unsafe { sys::imnodes_EndOutputAttribute() };
+    pub fn add_output<F: FnOnce() -> R, R>(&mut self, id: OutputPinId, shape: PinShape, f: F) -> R {
+        if self.disabled {
+            self.set_display_only(id);
+        }
+        let _token = AttributeToken::begin_output(id, shape);
+        f()
+    }
+
+    /// [ScopeNode::add_input] for a closure which can fail, EndInputAttribute is called before
+    /// the error is returned
+    pub fn add_input_try<F, T, E>(&mut self, id: InputPinId, shape: PinShape, f: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        self.add_input(id, shape, f)
+    }
+
+    /// [ScopeNode::add_output] for a closure which can fail, EndOutputAttribute is called before
+    /// the error is returned
+    pub fn add_output_try<F, T, E>(
+        &mut self,
+        id: OutputPinId,
+        shape: PinShape,
+        f: F,
+    ) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        self.add_output(id, shape, f)
+    }
+
+    /// all pins in a single row, e.g. for a collapsed node
+    ///
+    /// the inputs are drawn on top of each other at the left and the outputs at the right,
+    /// so links to any of them stay attached to the node
+    pub fn add_merged_pins(
+        &mut self,
+        inputs: &[(InputPinId, PinShape)],
+        outputs: &[(OutputPinId, PinShape)],
+    ) {
+        let mut row = imgui::sys::ImVec2::zero();
+        unsafe { imgui::sys::igGetCursorPos(&mut row) };
+        let size = imgui::sys::ImVec2::new(1.0, unsafe { imgui::sys::igGetTextLineHeight() });
+        for (id, shape) in inputs {
+            unsafe { imgui::sys::igSetCursorPos(row) };
+            self.add_input(*id, *shape, || unsafe { imgui::sys::igDummy(size) });
+        }
+        for (id, shape) in outputs {
+            unsafe { imgui::sys::igSetCursorPos(row) };
+            self.add_output(*id, *shape, || unsafe { imgui::sys::igDummy(size) });
+        }
     }
 
     /// BeginStaticAttribute
     /// ...
     /// EndStaticAttribute
-    pub fn attribute<F: FnOnce()>(&mut self, id: AttributeId, f: F) {
-        unsafe { sys::imnodes_BeginStaticAttribute(id.into()) };
-        f();
-        unsafe { sys::imnodes_EndStaticAttribute() };
+    pub fn attribute<F: FnOnce() -> R, R>(&mut self, id: AttributeId, f: F) -> R {
+        let _token = AttributeToken::begin_static(id);
+        f()
+    }
+
+    /// [ScopeNode::attribute] for a closure which can fail, EndStaticAttribute is called before
+    /// the error is returned
+    pub fn attribute_try<F, T, E>(&mut self, id: AttributeId, f: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        self.attribute(id, f)
+    }
+}
+
+/// the window draw list split into channels which are merged when this is dropped,
+/// also if a closure drawing into one of the channels panics
+///
+/// unlike the splitter of the draw list itself this can be nested in the channels of imnodes
+struct ChannelSplit {
+    splitter: *mut imgui::sys::ImDrawListSplitter,
+    draw_list: *mut imgui::sys::ImDrawList,
+}
+
+impl ChannelSplit {
+    fn new(count: i32) -> Self {
+        let draw_list = unsafe { imgui::sys::igGetWindowDrawList() };
+        let splitter = unsafe { imgui::sys::ImDrawListSplitter_ImDrawListSplitter() };
+        unsafe { imgui::sys::ImDrawListSplitter_Split(splitter, draw_list, count) };
+        Self {
+            splitter,
+            draw_list,
+        }
+    }
+
+    /// channel which is drawn into, later channels are drawn on top
+    fn set_channel(&self, channel: i32) {
+        unsafe {
+            imgui::sys::ImDrawListSplitter_SetCurrentChannel(self.splitter, self.draw_list, channel)
+        };
+    }
+}
+
+/// imgui `Alpha` multiplied with a factor until the token is dropped,
+/// which also happens while a panic unwinds
+struct AlphaToken;
+
+impl AlphaToken {
+    fn push(factor: f32) -> Self {
+        unsafe {
+            let alpha = (*imgui::sys::igGetStyle()).Alpha;
+            imgui::sys::igPushStyleVarFloat(imgui::sys::ImGuiStyleVar_Alpha as _, factor * alpha);
+        }
+        Self
+    }
+}
+
+impl Drop for AlphaToken {
+    fn drop(&mut self) {
+        unsafe { imgui::sys::igPopStyleVar(1) };
+    }
+}
+
+impl Drop for ChannelSplit {
+    fn drop(&mut self) {
+        unsafe {
+            imgui::sys::ImDrawListSplitter_Merge(self.splitter, self.draw_list);
+            imgui::sys::ImDrawListSplitter_destroy(self.splitter);
+        }
     }
 }
@@ -2,6 +2,10 @@
 
 use crate::{sys, EditorContext};
 use imgui::ImColor;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
 
 impl EditorContext {
     /// dark color theme
@@ -16,6 +20,22 @@ impl EditorContext {
         self
     }
 
+    /// is the flag set in the current style
+    pub fn style_flag(&self, flag: StyleFlag) -> bool {
+        self.get_style().flags & flag as u32 != 0
+    }
+
+    /// turn a flag of the current style on or off, e.g. to hide the grid lines at runtime
+    pub fn set_style_flag(&self, flag: StyleFlag, enabled: bool) -> &Self {
+        let style = self.get_style();
+        if enabled {
+            style.flags |= flag as u32;
+        } else {
+            style.flags &= !(flag as u32);
+        }
+        self
+    }
+
     /// light color theme
     pub fn set_style_colors_light(&self) -> &Self {
         unsafe { sys::imnodes_StyleColorsLight() };
@@ -23,7 +43,8 @@ impl EditorContext {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum ColorStyle {
     NodeBackground = sys::ColorStyle_ColorStyle_NodeBackground,
@@ -48,8 +69,34 @@ pub enum ColorStyle {
 impl ColorStyle {
     pub const COUNT: u32 = sys::ColorStyle_ColorStyle_Count;
 
+    #[cfg(feature = "serde")]
+    pub(crate) const ALL: [ColorStyle; 16] = [
+        ColorStyle::NodeBackground,
+        ColorStyle::NodeBackgroundHovered,
+        ColorStyle::NodeBackgroundSelected,
+        ColorStyle::NodeOutline,
+        ColorStyle::TitleBar,
+        ColorStyle::TitleBarHovered,
+        ColorStyle::TitleBarSelected,
+        ColorStyle::Link,
+        ColorStyle::LinkHovered,
+        ColorStyle::LinkSelected,
+        ColorStyle::Pin,
+        ColorStyle::PinHovered,
+        ColorStyle::BoxSelector,
+        ColorStyle::BoxSelectorOutline,
+        ColorStyle::GridBackground,
+        ColorStyle::GridLine,
+    ];
+
     #[must_use = "need to call pop on ColorToken befor going out of scope"]
     pub fn push_color<C: Into<ImColor>>(self, color: C, _: &EditorContext) -> ColorToken {
+        self.push(color)
+    }
+
+    /// for use inside the scopes where the [EditorContext] is borrowed by [editor](crate::editor)
+    #[must_use = "need to call pop on ColorToken befor going out of scope"]
+    pub(crate) fn push<C: Into<ImColor>>(self, color: C) -> ColorToken {
         let color: ImColor = color.into();
         unsafe { sys::imnodes_PushColorStyle(self as u32, color.into()) };
         ColorToken { ended: false }
@@ -72,12 +119,108 @@ impl ColorToken {
 impl Drop for ColorToken {
     fn drop(&mut self) {
         if !self.ended {
-            panic!("did not call pop on a color token");
+            if std::thread::panicking() {
+                // panicking again would abort, so clean up instead
+                unsafe { sys::imnodes_PopColorStyle() };
+            } else {
+                panic!("did not call pop on a color token");
+            }
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+/// colors of a single node, see [ScopeEditor::node_colored](crate::ScopeEditor::node_colored)
+///
+/// colors which are `None` keep the current style
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct NodeColors {
+    /// [ColorStyle::TitleBar]
+    pub title: Option<ImColor>,
+    /// [ColorStyle::TitleBarHovered]
+    pub title_hovered: Option<ImColor>,
+    /// [ColorStyle::TitleBarSelected]
+    pub title_selected: Option<ImColor>,
+    /// [ColorStyle::NodeBackground]
+    pub background: Option<ImColor>,
+}
+
+impl NodeColors {
+    pub(crate) fn push(&self) -> Vec<ColorToken> {
+        [
+            (ColorStyle::TitleBar, self.title),
+            (ColorStyle::TitleBarHovered, self.title_hovered),
+            (ColorStyle::TitleBarSelected, self.title_selected),
+            (ColorStyle::NodeBackground, self.background),
+        ]
+        .iter()
+        .filter_map(|(item, color)| color.map(|color| item.push(color)))
+        .collect()
+    }
+}
+
+/// node colors which are grayed out by
+/// [ScopeEditor::add_disabled_node](crate::ScopeEditor::add_disabled_node)
+const DISABLED_COLORS: [ColorStyle; 9] = [
+    ColorStyle::NodeBackground,
+    ColorStyle::NodeBackgroundHovered,
+    ColorStyle::NodeBackgroundSelected,
+    ColorStyle::NodeOutline,
+    ColorStyle::TitleBar,
+    ColorStyle::TitleBarHovered,
+    ColorStyle::TitleBarSelected,
+    ColorStyle::Pin,
+    ColorStyle::PinHovered,
+];
+
+/// how much darker the gray of a disabled color is than the brightness of the original
+const DISABLED_DIMMING: f32 = 0.6;
+
+/// dimmed gray version of a style color
+fn disabled_color(color: u32) -> ImColor {
+    let channel = |shift: u32| ((color >> shift) & 0xff) as f32 / 255.0;
+    let gray = DISABLED_DIMMING * (0.3 * channel(0) + 0.59 * channel(8) + 0.11 * channel(16));
+    ImColor::from([gray, gray, gray, channel(24)])
+}
+
+/// push the grayed out versions of the current node colors, pop them in reverse order
+pub(crate) fn push_disabled_colors() -> Vec<ColorToken> {
+    let style = unsafe { &*sys::imnodes_GetStyle() };
+    DISABLED_COLORS
+        .iter()
+        .map(|item| item.push(disabled_color(style.colors[*item as usize])))
+        .collect()
+}
+
+/// colors of a single link, see [ScopeEditor::add_link_styled](crate::ScopeEditor::add_link_styled)
+///
+/// colors which are `None` keep the current style.
+/// There is no thickness because imnodes reads [StyleVar::LinkThickness] when all links are
+/// drawn in EndNodeEditor, so it is the same for every link of an editor.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct LinkStyle {
+    /// [ColorStyle::Link]
+    pub color: Option<ImColor>,
+    /// [ColorStyle::LinkHovered]
+    pub hovered: Option<ImColor>,
+    /// [ColorStyle::LinkSelected]
+    pub selected: Option<ImColor>,
+}
+
+impl LinkStyle {
+    pub(crate) fn push(&self) -> Vec<ColorToken> {
+        [
+            (ColorStyle::Link, self.color),
+            (ColorStyle::LinkHovered, self.hovered),
+            (ColorStyle::LinkSelected, self.selected),
+        ]
+        .iter()
+        .filter_map(|(item, color)| color.map(|color| item.push(color)))
+        .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum StyleVar {
     GridSpacing = sys::StyleVar_StyleVar_GridSpacing,
@@ -97,8 +240,31 @@ pub enum StyleVar {
 }
 
 impl StyleVar {
+    #[cfg(feature = "serde")]
+    pub(crate) const ALL: [StyleVar; 14] = [
+        StyleVar::GridSpacing,
+        StyleVar::NodeCornerRounding,
+        StyleVar::NodePaddingHorizontal,
+        StyleVar::NodePaddingVertical,
+        StyleVar::NodeBorderThickness,
+        StyleVar::LinkThickness,
+        StyleVar::LinkLineSegmentsPerLength,
+        StyleVar::LinkHoverDistance,
+        StyleVar::PinCircleRadius,
+        StyleVar::PinQuadSideLength,
+        StyleVar::PinTriangleSideLength,
+        StyleVar::PinLineThickness,
+        StyleVar::PinHoverRadius,
+        StyleVar::PinOffset,
+    ];
     #[must_use = "need to call pop on StyleVarToken befor going out of scope"]
     pub fn push_val(self, value: f32, _: &EditorContext) -> StyleVarToken {
+        self.push(value)
+    }
+
+    /// for use inside the scopes where the [EditorContext] is borrowed by [editor](crate::editor)
+    #[must_use = "need to call pop on StyleVarToken befor going out of scope"]
+    pub(crate) fn push(self, value: f32) -> StyleVarToken {
         unsafe { sys::imnodes_PushStyleVar(self as u32, value) };
         StyleVarToken { ended: false }
     }
@@ -118,12 +284,22 @@ impl StyleVarToken {
 impl Drop for StyleVarToken {
     fn drop(&mut self) {
         if !self.ended {
-            panic!("did not call pop on a style var token");
+            if std::thread::panicking() {
+                // panicking again would abort, so clean up instead
+                unsafe { sys::imnodes_PopStyleVar() };
+            } else {
+                panic!("did not call pop on a style var token");
+            }
         }
     }
 }
 
+/// bits of the `flags` of the style, see [EditorContext::set_style_flag]
+///
+/// the bundled imnodes only knows these two, grid snapping and primary grid lines
+/// need a newer version
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum StyleFlag {
     // None = sys::StyleFlags_StyleFlags_None,
@@ -132,6 +308,7 @@ pub enum StyleFlag {
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum PinShape {
     Circle = sys::PinShape_PinShape_Circle,
@@ -173,7 +350,253 @@ impl AttributeFlagToken {
 impl Drop for AttributeFlagToken {
     fn drop(&mut self) {
         if !self.ended {
-            panic!("did not call pop on a style var token");
+            if std::thread::panicking() {
+                // panicking again would abort, so clean up instead
+                unsafe { sys::imnodes_PopAttributeFlag() };
+            } else {
+                panic!("did not call pop on a style var token");
+            }
+        }
+    }
+}
+
+/// all colors and style variables in one value
+///
+/// capture the current look with [Theme::capture], tweak it and switch to it with [Theme::apply]
+/// instead of pushing and popping every single color/ var each frame
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(from = "ThemeRepr", into = "ThemeRepr")
+)]
+pub struct Theme {
+    style: sys::Style,
+}
+
+/// [Theme] with every value stored by name, so the format does not depend on the order of the
+/// enums in imnodes
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct ThemeRepr {
+    /// `[r, g, b, a]` between 0 and 1
+    colors: BTreeMap<ColorStyle, [f32; 4]>,
+    vars: BTreeMap<StyleVar, f32>,
+    flags: Vec<StyleFlag>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Theme> for ThemeRepr {
+    fn from(theme: Theme) -> Self {
+        let colors = ColorStyle::ALL
+            .iter()
+            .map(|item| {
+                // imgui packs colors as ABGR
+                let color = theme.style.colors[*item as usize];
+                let channel = |shift: u32| ((color >> shift) & 0xff) as f32 / 255.0;
+                (*item, [channel(0), channel(8), channel(16), channel(24)])
+            })
+            .collect();
+        let vars = StyleVar::ALL
+            .iter()
+            .map(|item| (*item, theme.var(*item)))
+            .collect();
+        let flags = [StyleFlag::NodeOutline, StyleFlag::GridLines]
+            .iter()
+            .copied()
+            .filter(|flag| theme.has_flag(*flag))
+            .collect();
+        Self {
+            colors,
+            vars,
+            flags,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<ThemeRepr> for Theme {
+    fn from(repr: ThemeRepr) -> Self {
+        // colors and vars which are missing keep their default,
+        // the flags are the list of all enabled ones
+        let mut theme = Theme::default();
+        theme.style.flags = 0;
+        for (item, color) in repr.colors {
+            theme.set_color(item, color);
         }
+        for (item, value) in repr.vars {
+            theme.set_var(item, value);
+        }
+        for flag in repr.flags {
+            theme.set_flag(flag, true);
+        }
+        theme
+    }
+}
+
+/// imnodes_StyleColorsDark, packed as ABGR like IM_COL32
+const fn dark_color(r: u32, g: u32, b: u32, a: u32) -> u32 {
+    a << 24 | b << 16 | g << 8 | r
+}
+
+impl Default for Theme {
+    /// the style imnodes starts with, with the dark colors
+    fn default() -> Self {
+        Self {
+            style: sys::Style {
+                grid_spacing: 32.0,
+                node_corner_rounding: 4.0,
+                node_padding_horizontal: 8.0,
+                node_padding_vertical: 8.0,
+                node_border_thickness: 1.0,
+                link_thickness: 3.0,
+                link_line_segments_per_length: 0.1,
+                link_hover_distance: 10.0,
+                pin_circle_radius: 4.0,
+                pin_quad_side_length: 7.0,
+                pin_triangle_side_length: 9.5,
+                pin_line_thickness: 1.0,
+                pin_hover_radius: 10.0,
+                pin_offset: 0.0,
+                flags: StyleFlag::NodeOutline as u32 | StyleFlag::GridLines as u32,
+                colors: [
+                    dark_color(50, 50, 50, 255),
+                    dark_color(75, 75, 75, 255),
+                    dark_color(75, 75, 75, 255),
+                    dark_color(100, 100, 100, 255),
+                    dark_color(41, 74, 122, 255),
+                    dark_color(66, 150, 250, 255),
+                    dark_color(66, 150, 250, 255),
+                    dark_color(61, 133, 224, 200),
+                    dark_color(66, 150, 250, 255),
+                    dark_color(66, 150, 250, 255),
+                    dark_color(53, 150, 250, 180),
+                    dark_color(53, 150, 250, 255),
+                    dark_color(61, 133, 224, 30),
+                    dark_color(61, 133, 224, 150),
+                    dark_color(40, 40, 50, 200),
+                    dark_color(200, 200, 200, 40),
+                ],
+            },
+        }
+    }
+}
+
+impl Theme {
+    /// copy of the current global style
+    pub fn capture(context: &EditorContext) -> Self {
+        Self {
+            style: *context.get_style(),
+        }
+    }
+
+    /// overwrite the global style with this theme
+    pub fn apply(&self, context: &EditorContext) {
+        *context.get_style() = self.style;
+    }
+
+    /// color of one element
+    pub fn color(&self, item: ColorStyle) -> ImColor {
+        self.style.colors[item as usize].into()
+    }
+
+    /// change the color of one element
+    pub fn set_color<C: Into<ImColor>>(&mut self, item: ColorStyle, color: C) -> &mut Self {
+        let color: ImColor = color.into();
+        self.style.colors[item as usize] = color.into();
+        self
+    }
+
+    /// value of one style variable
+    pub fn var(&self, item: StyleVar) -> f32 {
+        let mut style = self.style;
+        *var_mut(&mut style, item)
+    }
+
+    /// change the value of one style variable
+    pub fn set_var(&mut self, item: StyleVar, value: f32) -> &mut Self {
+        *var_mut(&mut self.style, item) = value;
+        self
+    }
+
+    /// is the flag enabled
+    pub fn has_flag(&self, flag: StyleFlag) -> bool {
+        self.style.flags & flag as u32 != 0
+    }
+
+    /// enable or disable a flag
+    pub fn set_flag(&mut self, flag: StyleFlag, enabled: bool) -> &mut Self {
+        if enabled {
+            self.style.flags |= flag as u32;
+        } else {
+            self.style.flags &= !(flag as u32);
+        }
+        self
+    }
+}
+
+fn var_mut(style: &mut sys::Style, item: StyleVar) -> &mut f32 {
+    match item {
+        StyleVar::GridSpacing => &mut style.grid_spacing,
+        StyleVar::NodeCornerRounding => &mut style.node_corner_rounding,
+        StyleVar::NodePaddingHorizontal => &mut style.node_padding_horizontal,
+        StyleVar::NodePaddingVertical => &mut style.node_padding_vertical,
+        StyleVar::NodeBorderThickness => &mut style.node_border_thickness,
+        StyleVar::LinkThickness => &mut style.link_thickness,
+        StyleVar::LinkLineSegmentsPerLength => &mut style.link_line_segments_per_length,
+        StyleVar::LinkHoverDistance => &mut style.link_hover_distance,
+        StyleVar::PinCircleRadius => &mut style.pin_circle_radius,
+        StyleVar::PinQuadSideLength => &mut style.pin_quad_side_length,
+        StyleVar::PinTriangleSideLength => &mut style.pin_triangle_side_length,
+        StyleVar::PinLineThickness => &mut style.pin_line_thickness,
+        StyleVar::PinHoverRadius => &mut style.pin_hover_radius,
+        StyleVar::PinOffset => &mut style.pin_offset,
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_serde_round_trip() {
+        let mut theme = Theme::default();
+        theme
+            .set_color(ColorStyle::Link, dark_color(10, 20, 30, 40))
+            .set_var(StyleVar::GridSpacing, 16.0)
+            .set_flag(StyleFlag::GridLines, false);
+
+        let json = serde_json::to_string(&theme).unwrap();
+        let loaded: Theme = serde_json::from_str(&json).unwrap();
+        for item in ColorStyle::ALL.iter() {
+            let (color, loaded): (u32, u32) =
+                (theme.color(*item).into(), loaded.color(*item).into());
+            assert_eq!(color, loaded, "{:?}", item);
+        }
+        for item in StyleVar::ALL.iter() {
+            assert_eq!(theme.var(*item), loaded.var(*item), "{:?}", item);
+        }
+        assert!(loaded.has_flag(StyleFlag::NodeOutline));
+        assert!(!loaded.has_flag(StyleFlag::GridLines));
+    }
+
+    #[test]
+    fn missing_values_keep_the_default() {
+        let loaded: Theme = serde_json::from_str(
+            r#"{ "colors": {}, "vars": { "LinkThickness": 5.0 }, "flags": [] }"#,
+        )
+        .unwrap();
+        let default = Theme::default();
+        assert_eq!(loaded.var(StyleVar::LinkThickness), 5.0);
+        assert_eq!(
+            loaded.var(StyleVar::GridSpacing),
+            default.var(StyleVar::GridSpacing)
+        );
+        let (color, default): (u32, u32) = (
+            loaded.color(ColorStyle::TitleBar).into(),
+            default.color(ColorStyle::TitleBar).into(),
+        );
+        assert_eq!(color, default);
+        assert!(!loaded.has_flag(StyleFlag::NodeOutline));
     }
 }
@@ -0,0 +1,250 @@
+/*!
+Alternative to the closures of the [scopes](crate::ScopeEditor): every `begin_*` returns a token
+which calls the matching `End*` when it is dropped.
+
+This allows early returns, `?` and borrowing several values mutably inside a node.
+The tokens deref to the matching scope, so all scope methods are available on them.
+A token mutably borrows its parent, so the parent can not be used until the token is dropped.
+
+```ignore
+let mut editor = editor_begin(&ui, &mut context);
+for node in &graph.nodes {
+    let mut node_token = editor.begin_node(node.id);
+    {
+        let _title = node_token.begin_titlebar();
+        ui.text(&node.title);
+    }
+    let _input = node_token.begin_input(node.input, PinShape::Circle);
+    ui.text("in");
+}
+let scope = editor.end();
+```
+*/
+
+use crate::{
+    events::track_pending_link, scopes::begin_editor, sys, AttributeId, ColorStyle, EditorContext,
+    EditorContextGuard, InputPinId, NodeId, OutputPinId, PinId, PinShape, ScopeEditor, ScopeNode,
+    ScopeNone,
+};
+use imgui::ImColor;
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+/// BeginNodeEditor, like [editor](crate::editor) without a closure
+///
+/// `context` stays the current editor until the token and the [ScopeNone] returned by
+/// [EditorToken::end] are dropped
+pub fn editor_begin<'a>(ui: &imgui::Ui, context: &'a mut EditorContext) -> EditorToken<'a> {
+    let guard = begin_editor(ui, context);
+    let scope = ScopeEditor::new(guard.context);
+    EditorToken {
+        guard: Some(guard),
+        scope,
+    }
+}
+
+/// calls EndNodeEditor when dropped or ended with [EditorToken::end]
+#[derive(Debug)]
+pub struct EditorToken<'a> {
+    guard: Option<EditorContextGuard<'a>>,
+    scope: ScopeEditor<'a>,
+}
+
+impl<'a> EditorToken<'a> {
+    /// BeginNode
+    pub fn begin_node(&mut self, id: NodeId) -> NodeToken<'_> {
+        NodeToken::begin(id, self.scope.display_only_pins.clone())
+    }
+
+    /// EndNodeEditor, afterwards the queries which are only valid after the editor
+    /// are available on the returned scope
+    pub fn end(mut self) -> ScopeNone<'a> {
+        let guard = self
+            .guard
+            .take()
+            .expect("the editor token was already ended");
+        let display_only = self.scope.display_only_pins.take();
+        let hidden =
+            matches!(guard.context.pending_link.get(), Some(pin) if display_only.contains(&pin));
+
+        // the link which is dragged is drawn in EndNodeEditor, all others took their colors in Link
+        let color = if hidden {
+            self.scope.pending_link_color.take();
+            Some(ColorStyle::Link.push(ImColor::from([0.0, 0.0, 0.0, 0.0])))
+        } else {
+            self.scope
+                .pending_link_color
+                .take()
+                .map(|color| ColorStyle::Link.push(color))
+        };
+        unsafe { sys::imnodes_EndNodeEditor() };
+        if let Some(token) = color {
+            token.pop();
+        }
+        guard.context.display_only_pins.replace(display_only);
+        guard
+            .context
+            .disabled_nodes
+            .replace(self.scope.disabled_nodes.take());
+        track_pending_link(guard.context);
+        ScopeNone { guard }
+    }
+
+    /// another scope for the closure of [editor](crate::editor)
+    pub(crate) fn new_scope<'s>(&self) -> ScopeEditor<'s> {
+        self.scope.share()
+    }
+}
+
+impl<'a> Deref for EditorToken<'a> {
+    type Target = ScopeEditor<'a>;
+
+    fn deref(&self) -> &ScopeEditor<'a> {
+        &self.scope
+    }
+}
+
+impl<'a> DerefMut for EditorToken<'a> {
+    fn deref_mut(&mut self) -> &mut ScopeEditor<'a> {
+        &mut self.scope
+    }
+}
+
+impl Drop for EditorToken<'_> {
+    fn drop(&mut self) {
+        if self.guard.is_some() {
+            unsafe { sys::imnodes_EndNodeEditor() };
+        }
+    }
+}
+
+/// calls EndNode when dropped
+#[derive(Debug)]
+pub struct NodeToken<'e> {
+    scope: ScopeNode<'e>,
+    _editor: PhantomData<&'e mut ()>,
+}
+
+impl NodeToken<'_> {
+    pub(crate) fn begin(id: NodeId, display_only_pins: Rc<RefCell<HashSet<PinId>>>) -> Self {
+        unsafe { sys::imnodes_BeginNode(id.into()) };
+        NodeToken {
+            scope: ScopeNode::new(display_only_pins),
+            _editor: PhantomData,
+        }
+    }
+
+    /// BeginNodeTitleBar
+    pub fn begin_titlebar(&mut self) -> TitleBarToken<'_> {
+        TitleBarToken::begin()
+    }
+
+    /// BeginInputAttribute
+    pub fn begin_input(&mut self, id: InputPinId, shape: PinShape) -> AttributeToken<'_> {
+        AttributeToken::begin_input(id, shape)
+    }
+
+    /// BeginOutputAttribute
+    pub fn begin_output(&mut self, id: OutputPinId, shape: PinShape) -> AttributeToken<'_> {
+        AttributeToken::begin_output(id, shape)
+    }
+
+    /// BeginStaticAttribute
+    pub fn begin_attribute(&mut self, id: AttributeId) -> AttributeToken<'_> {
+        AttributeToken::begin_static(id)
+    }
+}
+
+impl<'e> Deref for NodeToken<'e> {
+    type Target = ScopeNode<'e>;
+
+    fn deref(&self) -> &ScopeNode<'e> {
+        &self.scope
+    }
+}
+
+impl<'e> DerefMut for NodeToken<'e> {
+    fn deref_mut(&mut self) -> &mut ScopeNode<'e> {
+        &mut self.scope
+    }
+}
+
+impl Drop for NodeToken<'_> {
+    fn drop(&mut self) {
+        unsafe { sys::imnodes_EndNode() };
+    }
+}
+
+/// calls EndNodeTitleBar when dropped
+#[derive(Debug)]
+pub struct TitleBarToken<'n> {
+    _node: PhantomData<&'n mut ()>,
+}
+
+impl TitleBarToken<'_> {
+    pub(crate) fn begin() -> Self {
+        unsafe { sys::imnodes_BeginNodeTitleBar() };
+        TitleBarToken { _node: PhantomData }
+    }
+}
+
+impl Drop for TitleBarToken<'_> {
+    fn drop(&mut self) {
+        unsafe { sys::imnodes_EndNodeTitleBar() };
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum AttributeKind {
+    Input,
+    Output,
+    Static,
+}
+
+/// calls EndInputAttribute, EndOutputAttribute or EndStaticAttribute when dropped
+#[derive(Debug)]
+pub struct AttributeToken<'n> {
+    kind: AttributeKind,
+    _node: PhantomData<&'n mut ()>,
+}
+
+impl AttributeToken<'_> {
+    pub(crate) fn begin_input(id: InputPinId, shape: PinShape) -> Self {
+        unsafe { sys::imnodes_BeginInputAttribute(id.into(), shape as u32) };
+        AttributeToken {
+            kind: AttributeKind::Input,
+            _node: PhantomData,
+        }
+    }
+
+    pub(crate) fn begin_output(id: OutputPinId, shape: PinShape) -> Self {
+        unsafe { sys::imnodes_BeginOutputAttribute(id.into(), shape as u32) };
+        AttributeToken {
+            kind: AttributeKind::Output,
+            _node: PhantomData,
+        }
+    }
+
+    pub(crate) fn begin_static(id: AttributeId) -> Self {
+        unsafe { sys::imnodes_BeginStaticAttribute(id.into()) };
+        AttributeToken {
+            kind: AttributeKind::Static,
+            _node: PhantomData,
+        }
+    }
+}
+
+impl Drop for AttributeToken<'_> {
+    fn drop(&mut self) {
+        match self.kind {
+            AttributeKind::Input => unsafe { sys::imnodes_EndInputAttribute() },
+            AttributeKind::Output => unsafe { sys::imnodes_EndOutputAttribute() },
+            AttributeKind::Static => unsafe { sys::imnodes_EndStaticAttribute() },
+        }
+    }
+}
@@ -0,0 +1,243 @@
+/*!
+Pins with a type, e.g. `float`, `vec3` or `texture` in a shader editor.
+
+[PinTypes] maps pin ids to a type chosen by the application, any `Copy + Eq + Hash` value
+like a plain enum or [std::any::TypeId] works.
+Created links can be checked against the types of their pins and every type can have a color
+and a shape which are used for its pins, so all pins of a type look the same in the whole editor.
+
+```ignore
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum Value { Float, Color }
+
+let mut types = PinTypes::new();
+types
+    .set(input, Value::Float)
+    .set(output, Value::Color)
+    .set_color(Value::Float, [0.4, 0.7, 0.2])
+    .set_color(Value::Color, [0.8, 0.3, 0.6])
+    .set_shape(Value::Color, PinShape::QuadFilled)
+    .set_link_coloring(LinkColoring::Gradient);
+
+if let Some(created) = types.links_created(&scope) {
+    if created.matches {
+        // add the link
+    }
+}
+```
+
+[Graph::render_typed](crate::graph::Graph::render_typed) does both for a retained graph.
+*/
+
+use crate::{
+    ColorStyle, InputPinId, Link, LinkId, LinkStyle, OutputPinId, PinId, PinShape, ScopeEditor,
+    ScopeNode, ScopeNone,
+};
+use imgui::ImColor;
+use std::{collections::HashMap, hash::Hash};
+
+/// link creation event together with the types of both pins
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TypedLink<T> {
+    /// the created link
+    pub link: Link,
+    /// type of the output pin, if it has one
+    pub start_type: Option<T>,
+    /// type of the input pin, if it has one
+    pub end_type: Option<T>,
+    /// see [PinTypes::accepts]
+    pub matches: bool,
+}
+
+/// how [Graph::render_typed](crate::graph::Graph::render_typed) and [PinTypes::add_link]
+/// color links, see [PinTypes::set_link_coloring]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum LinkColoring {
+    /// the link colors of the style
+    #[default]
+    Style,
+    /// the color of the type of the output pin
+    Source,
+    /// from the color of the output type to the color of the input type, only
+    /// [Graph::render_typed](crate::graph::Graph::render_typed) can draw it,
+    /// [PinTypes::add_link] uses [LinkColoring::Source] instead
+    Gradient,
+}
+
+/// types and type colors of pins
+#[derive(Debug, Clone)]
+pub struct PinTypes<T> {
+    types: HashMap<PinId, T>,
+    colors: HashMap<T, ImColor>,
+    shapes: HashMap<T, PinShape>,
+    link_coloring: LinkColoring,
+}
+
+impl<T: Copy + Eq + Hash> Default for PinTypes<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + Eq + Hash> PinTypes<T> {
+    /// no typed pins
+    pub fn new() -> Self {
+        Self {
+            types: HashMap::new(),
+            colors: HashMap::new(),
+            shapes: HashMap::new(),
+            link_coloring: LinkColoring::Style,
+        }
+    }
+
+    /// set the type of an input or output pin
+    pub fn set<P: Into<PinId>>(&mut self, pin: P, pin_type: T) -> &mut Self {
+        self.types.insert(pin.into(), pin_type);
+        self
+    }
+
+    /// forget the type of a pin, e.g. after its node was removed
+    pub fn remove<P: Into<PinId>>(&mut self, pin: P) -> Option<T> {
+        self.types.remove(&pin.into())
+    }
+
+    /// type of an input or output pin
+    pub fn get<P: Into<PinId>>(&self, pin: P) -> Option<T> {
+        self.types.get(&pin.into()).copied()
+    }
+
+    /// color used for all pins of this type
+    pub fn set_color<C: Into<ImColor>>(&mut self, pin_type: T, color: C) -> &mut Self {
+        self.colors.insert(pin_type, color.into());
+        self
+    }
+
+    /// color of a type set with [PinTypes::set_color]
+    pub fn color(&self, pin_type: T) -> Option<ImColor> {
+        self.colors.get(&pin_type).copied()
+    }
+
+    /// color of the type of a pin
+    pub fn color_of<P: Into<PinId>>(&self, pin: P) -> Option<ImColor> {
+        self.get(pin).and_then(|pin_type| self.color(pin_type))
+    }
+
+    /// shape used for all pins of this type instead of the one passed to
+    /// [PinTypes::add_input] and [PinTypes::add_output]
+    pub fn set_shape(&mut self, pin_type: T, shape: PinShape) -> &mut Self {
+        self.shapes.insert(pin_type, shape);
+        self
+    }
+
+    /// shape of a type set with [PinTypes::set_shape]
+    pub fn shape(&self, pin_type: T) -> Option<PinShape> {
+        self.shapes.get(&pin_type).copied()
+    }
+
+    /// shape of the type of a pin
+    pub fn shape_of<P: Into<PinId>>(&self, pin: P) -> Option<PinShape> {
+        self.get(pin).and_then(|pin_type| self.shape(pin_type))
+    }
+
+    /// color links by the types of their pins, hovered and selected links keep
+    /// the colors of the style
+    pub fn set_link_coloring(&mut self, coloring: LinkColoring) -> &mut Self {
+        self.link_coloring = coloring;
+        self
+    }
+
+    /// coloring set with [PinTypes::set_link_coloring]
+    pub fn link_coloring(&self) -> LinkColoring {
+        self.link_coloring
+    }
+
+    /// colors at the output and at the input end of a link for the [LinkColoring],
+    /// `None` if the link keeps the colors of the style
+    pub fn link_colors(&self, start: OutputPinId, end: InputPinId) -> Option<(ImColor, ImColor)> {
+        match self.link_coloring {
+            LinkColoring::Style => None,
+            LinkColoring::Source => self.color_of(start).map(|color| (color, color)),
+            LinkColoring::Gradient => match (self.color_of(start), self.color_of(end)) {
+                (Some(start), Some(end)) => Some((start, end)),
+                (Some(color), None) | (None, Some(color)) => Some((color, color)),
+                (None, None) => None,
+            },
+        }
+    }
+
+    /// [ScopeEditor::add_link] colored by the type of the output pin
+    /// as chosen with [PinTypes::set_link_coloring]
+    pub fn add_link(
+        &self,
+        scope: &ScopeEditor,
+        id: LinkId,
+        input: InputPinId,
+        output: OutputPinId,
+    ) {
+        match self.link_colors(output, input) {
+            Some((color, _)) => {
+                let style = LinkStyle {
+                    color: Some(color),
+                    ..LinkStyle::default()
+                };
+                scope.add_link_styled(id, input, output, style);
+            }
+            None => scope.add_link(id, input, output),
+        }
+    }
+
+    /// true if both pins have the same type or at least one of them has no type
+    pub fn accepts(&self, link: &Link) -> bool {
+        match (self.get(link.start_pin), self.get(link.end_pin)) {
+            (Some(start), Some(end)) => start == end,
+            _ => true,
+        }
+    }
+
+    /// annotate a link creation event with the types of its pins
+    pub fn check(&self, link: Link) -> TypedLink<T> {
+        TypedLink {
+            link,
+            start_type: self.get(link.start_pin),
+            end_type: self.get(link.end_pin),
+            matches: self.accepts(&link),
+        }
+    }
+
+    /// [ScopeNone::links_created] annotated with the pin types
+    pub fn links_created(&self, scope: &ScopeNone) -> Option<TypedLink<T>> {
+        scope.links_created().map(|link| self.check(link))
+    }
+
+    /// [ScopeNode::add_input] drawn in the color of the pin type,
+    /// `shape` is only used if the type has no shape
+    pub fn add_input<F: FnOnce()>(
+        &self,
+        scope: &mut ScopeNode<'_>,
+        id: InputPinId,
+        shape: PinShape,
+        f: F,
+    ) {
+        let token = self.color_of(id).map(|color| ColorStyle::Pin.push(color));
+        scope.add_input(id, self.shape_of(id).unwrap_or(shape), f);
+        if let Some(token) = token {
+            token.pop();
+        }
+    }
+
+    /// [ScopeNode::add_output] drawn in the color of the pin type,
+    /// `shape` is only used if the type has no shape
+    pub fn add_output<F: FnOnce()>(
+        &self,
+        scope: &mut ScopeNode<'_>,
+        id: OutputPinId,
+        shape: PinShape,
+        f: F,
+    ) {
+        let token = self.color_of(id).map(|color| ColorStyle::Pin.push(color));
+        scope.add_output(id, self.shape_of(id).unwrap_or(shape), f);
+        if let Some(token) = token {
+            token.pop();
+        }
+    }
+}
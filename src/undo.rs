@@ -0,0 +1,484 @@
+/*!
+Undo/ redo for a [Graph].
+
+[UndoStack::track] is called once per frame with the [ScopeNone] returned by [Graph::render]
+after the graph was modified. It records every difference since the last step as one undo step,
+no matter if it came from user interaction (links created or detached, nodes dragged)
+or from application code (nodes added or removed).
+
+The nodes and links are only compared when the graph changed since the last call,
+positions are only polled for the selected nodes because those are the only ones the user
+can drag. Nodes moved by the application while they are not selected are not recorded.
+*/
+
+use crate::{
+    graph::{Graph, Link, Node},
+    CoordinateSystem, EditorContext, InputPinId, NodeId, OutputPinId, ScopeNone,
+};
+use imgui::{MouseButton, Ui};
+use std::collections::{HashMap, HashSet};
+
+/// one recorded modification of a [Graph]
+#[derive(Debug, Clone)]
+pub enum Edit<N> {
+    /// node was added
+    AddNode(Node<N>),
+    /// node was removed together with its links
+    RemoveNode {
+        /// the removed node
+        node: Node<N>,
+        /// index it had in [Graph::nodes]
+        index: usize,
+        /// grid space position it had
+        position: [f32; 2],
+        /// links to and from its pins which were removed with it
+        links: Vec<Link>,
+    },
+    /// link was created
+    AddLink(Link),
+    /// link was destroyed
+    RemoveLink(Link),
+    /// node was moved in grid space
+    MoveNode {
+        /// the moved node
+        id: NodeId,
+        /// position before
+        from: [f32; 2],
+        /// position after
+        to: [f32; 2],
+    },
+}
+
+#[derive(Debug)]
+struct Snapshot<N> {
+    /// [Graph::revision] the copy was taken at
+    revision: u64,
+    nodes: Vec<Node<N>>,
+    links: Vec<Link>,
+}
+
+impl<N: Clone> Snapshot<N> {
+    fn of(graph: &Graph<N>) -> Self {
+        Self {
+            revision: graph.revision(),
+            nodes: graph.nodes().to_vec(),
+            links: graph.links().to_vec(),
+        }
+    }
+}
+
+/// records the changes of a [Graph] and can revert/ replay them
+#[derive(Debug)]
+pub struct UndoStack<N> {
+    undo: Vec<Vec<Edit<N>>>,
+    redo: Vec<Vec<Edit<N>>>,
+    snapshot: Option<Snapshot<N>>,
+    /// grid space position of every node as of the last step
+    positions: HashMap<NodeId, [f32; 2]>,
+}
+
+impl<N> Default for UndoStack<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N> UndoStack<N> {
+    /// empty stack, the first [UndoStack::track] only takes a snapshot
+    pub fn new() -> Self {
+        Self {
+            undo: vec![],
+            redo: vec![],
+            snapshot: None,
+            positions: HashMap::new(),
+        }
+    }
+
+    /// is there anything to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// is there anything to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// forget all recorded steps
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+impl<N: Clone> UndoStack<N> {
+    /// record everything which changed since the last call as one undo step
+    ///
+    /// nothing is recorded while the left mouse button is down, so dragging a node
+    /// over many frames ends up as a single step once it is released
+    pub fn track(&mut self, ui: &Ui, graph: &Graph<N>, scope: &ScopeNone) {
+        if ui.is_mouse_down(MouseButton::Left) {
+            return;
+        }
+
+        let mut edits = vec![];
+        let previous = self.snapshot.take();
+        let changed = match &previous {
+            Some(previous) => previous.revision != graph.revision(),
+            None => true,
+        };
+        if !changed {
+            self.snapshot = previous;
+        } else {
+            let current = Snapshot::of(graph);
+            if let Some(previous) = previous {
+                edits = diff(&previous, &current, &self.positions);
+            }
+            let ids: HashSet<NodeId> = current.nodes.iter().map(|node| node.id).collect();
+            self.positions.retain(|id, _| ids.contains(id));
+            for id in ids {
+                self.positions
+                    .entry(id)
+                    .or_insert_with(|| grid_position(id));
+            }
+            self.snapshot = Some(current);
+        }
+
+        for id in scope.selected_nodes() {
+            if let Some(from) = self.positions.get_mut(&id) {
+                let to = grid_position(id);
+                if *from != to {
+                    edits.push(Edit::MoveNode {
+                        id,
+                        from: *from,
+                        to,
+                    });
+                    *from = to;
+                }
+            }
+        }
+
+        if !edits.is_empty() {
+            self.undo.push(edits);
+            self.redo.clear();
+        }
+    }
+
+    /// revert the last step, returns false if there was nothing to undo
+    pub fn undo(&mut self, graph: &mut Graph<N>, context: &EditorContext) -> bool {
+        let edits = match self.undo.pop() {
+            Some(edits) => edits,
+            None => return false,
+        };
+        for edit in edits.iter().rev() {
+            if let Some((id, position)) = revert(edit, graph) {
+                self.set_position(context, id, position);
+            }
+        }
+        self.redo.push(edits);
+        self.snapshot = Some(Snapshot::of(graph));
+        true
+    }
+
+    /// replay the last reverted step, returns false if there was nothing to redo
+    pub fn redo(&mut self, graph: &mut Graph<N>, context: &EditorContext) -> bool {
+        let edits = match self.redo.pop() {
+            Some(edits) => edits,
+            None => return false,
+        };
+        for edit in edits.iter() {
+            if let Some((id, position)) = replay(edit, graph) {
+                self.set_position(context, id, position);
+            }
+        }
+        self.undo.push(edits);
+        self.snapshot = Some(Snapshot::of(graph));
+        true
+    }
+
+    /// move the node in the editor and remember the position, so it is not recorded as a move
+    fn set_position(&mut self, context: &EditorContext, id: NodeId, [x, y]: [f32; 2]) {
+        let _previous = context.make_current();
+        id.set_position(x, y, CoordinateSystem::GridSpace);
+        self.positions.insert(id, [x, y]);
+    }
+}
+
+fn grid_position(id: NodeId) -> [f32; 2] {
+    let position = id.get_position(CoordinateSystem::GridSpace);
+    [position.x, position.y]
+}
+
+/// added and removed nodes and links, `positions` are the grid space positions of the
+/// previous nodes
+fn diff<N: Clone>(
+    previous: &Snapshot<N>,
+    current: &Snapshot<N>,
+    positions: &HashMap<NodeId, [f32; 2]>,
+) -> Vec<Edit<N>> {
+    let mut edits = vec![];
+    let previous_nodes: HashSet<NodeId> = previous.nodes.iter().map(|node| node.id).collect();
+    let current_nodes: HashSet<NodeId> = current.nodes.iter().map(|node| node.id).collect();
+    let previous_links: HashSet<Link> = previous.links.iter().copied().collect();
+    let current_links: HashSet<Link> = current.links.iter().copied().collect();
+
+    // pins of the removed nodes, so their links are recorded with them
+    let mut removed_inputs: HashMap<InputPinId, NodeId> = HashMap::new();
+    let mut removed_outputs: HashMap<OutputPinId, NodeId> = HashMap::new();
+    for node in &previous.nodes {
+        if !current_nodes.contains(&node.id) {
+            removed_inputs.extend(node.inputs.iter().map(|pin| (pin.id, node.id)));
+            removed_outputs.extend(node.outputs.iter().map(|pin| (pin.id, node.id)));
+        }
+    }
+    let mut node_links: HashMap<NodeId, Vec<Link>> = HashMap::new();
+    let mut removed_links = vec![];
+    for link in &previous.links {
+        if current_links.contains(link) {
+            continue;
+        }
+        let owner = removed_inputs
+            .get(&link.end)
+            .or_else(|| removed_outputs.get(&link.start));
+        match owner {
+            Some(owner) => node_links.entry(*owner).or_default().push(*link),
+            None => removed_links.push(*link),
+        }
+    }
+
+    // last index first, so reverting the edits in reverse order inserts every node
+    // into a list which already contains all nodes before it
+    for (index, node) in previous.nodes.iter().enumerate().rev() {
+        if !current_nodes.contains(&node.id) {
+            edits.push(Edit::RemoveNode {
+                node: node.clone(),
+                index,
+                position: positions.get(&node.id).copied().unwrap_or_default(),
+                links: node_links.remove(&node.id).unwrap_or_default(),
+            });
+        }
+    }
+    edits.extend(removed_links.into_iter().map(Edit::RemoveLink));
+    for node in &current.nodes {
+        if !previous_nodes.contains(&node.id) {
+            edits.push(Edit::AddNode(node.clone()));
+        }
+    }
+    for link in &current.links {
+        if !previous_links.contains(link) {
+            edits.push(Edit::AddLink(*link));
+        }
+    }
+
+    edits
+}
+
+/// undo one edit on the graph, returns the node which has to be moved and where to
+fn revert<N: Clone>(edit: &Edit<N>, graph: &mut Graph<N>) -> Option<(NodeId, [f32; 2])> {
+    match edit {
+        Edit::AddNode(node) => {
+            graph.remove_node(node.id);
+            None
+        }
+        Edit::RemoveNode {
+            node,
+            index,
+            position,
+            links,
+        } => {
+            graph.insert_node_at(*index, node.clone());
+            for link in links {
+                graph.insert_link(*link);
+            }
+            Some((node.id, *position))
+        }
+        Edit::AddLink(link) => {
+            graph.remove_link(link.id);
+            None
+        }
+        Edit::RemoveLink(link) => {
+            graph.insert_link(*link);
+            None
+        }
+        Edit::MoveNode { id, from, .. } => Some((*id, *from)),
+    }
+}
+
+/// redo one edit on the graph, returns the node which has to be moved and where to
+fn replay<N: Clone>(edit: &Edit<N>, graph: &mut Graph<N>) -> Option<(NodeId, [f32; 2])> {
+    match edit {
+        Edit::AddNode(node) => graph.insert_node(node.clone()),
+        Edit::RemoveNode { node, .. } => {
+            graph.remove_node(node.id);
+        }
+        Edit::AddLink(link) => graph.insert_link(*link),
+        Edit::RemoveLink(link) => {
+            graph.remove_link(link.id);
+        }
+        Edit::MoveNode { id, to, .. } => return Some((*id, *to)),
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LinkId, PinShape};
+
+    /// grid space position of every node derived from its id
+    fn positions_of(graph: &Graph<()>) -> HashMap<NodeId, [f32; 2]> {
+        graph
+            .nodes()
+            .iter()
+            .map(|node| (node.id, [node.id.id as f32 * 100.0, 0.0]))
+            .collect()
+    }
+
+    /// chain of `count` nodes, each linked to the next one
+    fn chain(count: usize) -> Graph<()> {
+        let mut graph = Graph::new();
+        let mut previous = None;
+        for i in 0..count {
+            let id = graph.add_node(format!("node {}", i), ());
+            let input = graph.add_input(id, "in", PinShape::Circle);
+            let output = graph.add_output(id, "out", PinShape::Circle);
+            if let Some(previous) = previous {
+                graph.add_link(previous, input);
+            }
+            previous = Some(output);
+        }
+        graph
+    }
+
+    fn node_ids(graph: &Graph<()>) -> Vec<NodeId> {
+        graph.nodes().iter().map(|node| node.id).collect()
+    }
+
+    fn sorted_links(graph: &Graph<()>) -> Vec<LinkId> {
+        let mut links: Vec<LinkId> = graph.links().iter().map(|link| link.id).collect();
+        links.sort_by_key(|link| link.id);
+        links
+    }
+
+    #[test]
+    fn removed_node_records_its_index_position_and_links() {
+        let mut graph = chain(3);
+        let previous = Snapshot::of(&graph);
+        let positions = positions_of(&graph);
+        let middle = graph.nodes()[1].id;
+        let links = graph.links().to_vec();
+        graph.remove_node(middle);
+
+        let edits = diff(&previous, &Snapshot::of(&graph), &positions);
+        assert_eq!(edits.len(), 1);
+        match &edits[0] {
+            Edit::RemoveNode {
+                node,
+                index,
+                position,
+                links: removed,
+            } => {
+                assert_eq!(node.id, middle);
+                assert_eq!(*index, 1);
+                assert_eq!(*position, [middle.id as f32 * 100.0, 0.0]);
+                assert_eq!(removed, &links);
+            }
+            edit => panic!("unexpected edit {:?}", edit),
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_restore_nodes_and_links() {
+        let mut graph = chain(5);
+        let before = (node_ids(&graph), sorted_links(&graph));
+        let previous = Snapshot::of(&graph);
+        let positions = positions_of(&graph);
+        let removed = [graph.nodes()[1].id, graph.nodes()[3].id];
+        for id in &removed {
+            graph.remove_node(*id);
+        }
+        let after = (node_ids(&graph), sorted_links(&graph));
+
+        let edits = diff(&previous, &Snapshot::of(&graph), &positions);
+        let mut moved: Vec<NodeId> = edits
+            .iter()
+            .rev()
+            .filter_map(|edit| revert(edit, &mut graph))
+            .map(|(id, _)| id)
+            .collect();
+        moved.sort_by_key(|id| id.id);
+        assert_eq!(moved, removed);
+        assert_eq!((node_ids(&graph), sorted_links(&graph)), before);
+
+        for edit in &edits {
+            assert!(replay(edit, &mut graph).is_none());
+        }
+        assert_eq!((node_ids(&graph), sorted_links(&graph)), after);
+    }
+
+    #[test]
+    fn added_links_and_moves_are_reverted_and_replayed() {
+        let mut graph = chain(2);
+        let previous = Snapshot::of(&graph);
+        let start = graph.nodes()[1].outputs[0].id;
+        let end = graph.nodes()[0].inputs[0].id;
+        let link = graph.add_link(start, end);
+        let first = graph.nodes()[0].id;
+
+        let mut edits = diff(&previous, &Snapshot::of(&graph), &positions_of(&graph));
+        assert_eq!(edits.len(), 1);
+        edits.push(Edit::MoveNode {
+            id: first,
+            from: [0.0, 0.0],
+            to: [10.0, 20.0],
+        });
+
+        let positions: Vec<_> = edits
+            .iter()
+            .rev()
+            .filter_map(|edit| revert(edit, &mut graph))
+            .collect();
+        assert_eq!(positions, vec![(first, [0.0, 0.0])]);
+        assert!(graph.link(link).is_none());
+
+        let positions: Vec<_> = edits
+            .iter()
+            .filter_map(|edit| replay(edit, &mut graph))
+            .collect();
+        assert_eq!(positions, vec![(first, [10.0, 20.0])]);
+        assert!(graph.link(link).is_some());
+    }
+
+    #[test]
+    fn links_without_a_removed_node_are_recorded_on_their_own() {
+        let mut graph = chain(3);
+        let previous = Snapshot::of(&graph);
+        let positions = positions_of(&graph);
+        let links = graph.links().to_vec();
+        graph.remove_link(links[0].id);
+        graph.remove_node(graph.nodes()[2].id);
+
+        let edits = diff(&previous, &Snapshot::of(&graph), &positions);
+        assert_eq!(edits.len(), 2);
+        assert!(
+            matches!(&edits[0], Edit::RemoveNode { links: removed, .. } if removed == &links[1..])
+        );
+        assert!(matches!(&edits[1], Edit::RemoveLink(link) if *link == links[0]));
+    }
+
+    #[test]
+    fn revision_only_changes_with_the_graph() {
+        let mut graph = chain(2);
+        let revision = graph.revision();
+        let _ = (
+            graph.nodes(),
+            graph.links(),
+            graph.node(graph.nodes()[0].id),
+        );
+        assert_eq!(graph.revision(), revision);
+
+        let link = graph.links()[0].id;
+        graph.remove_link(link);
+        assert_ne!(graph.revision(), revision);
+    }
+}